@@ -28,24 +28,6 @@ fn load_file(file_path: &str) -> (Vec<Vec<f64>>, Vec<f32>) {
     (features, labels)
 }
 
-fn argmax<T: PartialOrd>(xs: &[T]) -> usize {
-    if xs.len() == 1 {
-        0
-    } else {
-        let mut maxval = &xs[0];
-        let mut max_ixs: Vec<usize> = vec![0];
-        for (i, x) in xs.iter().enumerate().skip(1) {
-            if x > maxval {
-                maxval = x;
-                max_ixs = vec![i];
-            } else if x == maxval {
-                max_ixs.push(i);
-            }
-        }
-        max_ixs[0]
-    }
-}
-
 fn main() -> std::io::Result<()> {
     let (train_features, train_labels) = load_file(
         "../../lightgbm-sys/lightgbm/examples/multiclass_classification/multiclass.train",
@@ -64,16 +46,15 @@ fn main() -> std::io::Result<()> {
     };
 
     let booster = Booster::train(train_dataset, &params).unwrap();
-    let result = booster.predict(test_features).unwrap();
+    let result = booster.predict_mat(&test_features).unwrap();
 
     let mut tp = 0;
-    for (label, pred) in zip(&test_labels, &result) {
-        let argmax_pred = argmax(&pred);
+    for (label, (argmax_pred, pred)) in zip(&test_labels, zip(result.argmax(), result.rows())) {
         if *label == argmax_pred as f32 {
             tp += 1;
         }
-        println!("{}, {}, {:?}", label, argmax_pred, &pred);
+        println!("{}, {}, {:?}", label, argmax_pred, pred);
     }
-    println!("{} / {}", &tp, result.len());
+    println!("{} / {}", &tp, test_labels.len());
     Ok(())
 }
@@ -1,21 +1,32 @@
 use libc::{c_char, c_double, c_longlong, c_void};
 use std;
-use std::ffi::CString;
-
-use serde_json::Value;
+use std::ffi::{CStr, CString};
 
 use lightgbm_sys;
 
-use crate::{Dataset, Error, Result};
+use crate::dataset::check_compressed;
+use crate::train::{EarlyStopping, EvalHistory, TrainOptions, Trainer};
+use crate::{
+    CallbackAction, DataType, Dataset, Error, IndexType, PredictOptions, PredictType, Predictions,
+    Result, SparseContributions, ToParams,
+};
 
 /// Core model in LightGBM, containing functions for training, evaluating and predicting.
 pub struct Booster {
-    handle: lightgbm_sys::BoosterHandle,
+    pub(crate) handle: lightgbm_sys::BoosterHandle,
+    pub(crate) eval_history: EvalHistory,
+    pub(crate) best_iteration: Option<i32>,
+    pub(crate) best_score: Option<f64>,
 }
 
 impl Booster {
-    fn new(handle: lightgbm_sys::BoosterHandle) -> Self {
-        Booster { handle }
+    pub(crate) fn new(handle: lightgbm_sys::BoosterHandle) -> Self {
+        Booster {
+            handle,
+            eval_history: EvalHistory::new(),
+            best_iteration: None,
+            best_score: None,
+        }
     }
 
     /// Init from model file.
@@ -34,6 +45,9 @@ impl Booster {
 
     /// Create a new Booster model with given Dataset and parameters.
     ///
+    /// The parameters are a [`TrainParams`](crate::TrainParams) or a JSON object
+    /// of LightGBM parameters, which is validated the same way.
+    ///
     /// Example
     /// ```
     /// extern crate serde_json;
@@ -56,43 +70,204 @@ impl Booster {
     /// };
     /// let bst = Booster::train(dataset, &params).unwrap();
     /// ```
-    pub fn train(dataset: Dataset, parameter: &Value) -> Result<Self> {
-        // get num_iterations
-        let num_iterations: i64 = if parameter["num_iterations"].is_null() {
-            100
-        } else {
-            parameter["num_iterations"].as_i64().unwrap()
-        };
+    pub fn train<P: ToParams + ?Sized>(dataset: Dataset, parameter: &P) -> Result<Self> {
+        Self::train_with_options(dataset, parameter, TrainOptions::new())
+    }
 
-        // exchange params {"x": "y", "z": 1} => "x=y z=1"
-        let params_string = parameter
-            .as_object()
-            .unwrap()
-            .iter()
-            .map(|(k, v)| format!("{}={}", k, v))
-            .collect::<Vec<_>>()
-            .join(" ");
-        let params_cstring = CString::new(params_string).unwrap();
+    /// Create a new Booster model with given Dataset, parameters and training options.
+    ///
+    /// Every validation dataset in `options` is evaluated after each iteration
+    /// with the metrics given in the parameters, and the values are recorded in
+    /// [`Booster::eval_history`]. Validation datasets must be binned like the
+    /// training dataset, i.e. created with it as their reference such as with
+    /// [`Dataset::from_file_with_reference`], otherwise LightGBM rejects them.
+    ///
+    /// If `early_stopping_round` is set, training stops once no validation metric
+    /// has improved for that many rounds (only the first metric is checked if
+    /// `first_metric_only` is `true`, and improvements smaller than
+    /// `early_stopping_min_delta` are ignored). The best iteration is then used
+    /// by default for prediction and saving. Training also stops early when
+    /// LightGBM cannot find any more splits.
+    ///
+    /// A custom [`Objective`](crate::Objective) in `options` is used to compute
+    /// the gradients of every iteration instead of the `objective` parameter,
+    /// and custom [`Metric`](crate::Metric)s are evaluated and recorded after
+    /// the built-in metrics.
+    ///
+    /// [`TrainingCallback`](crate::TrainingCallback)s in `options` are invoked
+    /// before and after every iteration and may stop training.
+    ///
+    /// Example
+    /// ```
+    /// extern crate serde_json;
+    /// use lightgbm::{Dataset, Booster, TrainOptions};
+    /// use serde_json::json;
+    ///
+    /// let train = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train").unwrap();
+    /// let valid = Dataset::from_file_with_reference(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.test", &train).unwrap();
+    /// let params = json!{
+    ///    {
+    ///         "num_iterations": 10,
+    ///         "objective": "binary",
+    ///         "metric": "auc,binary_logloss"
+    ///     }
+    /// };
+    /// let options = TrainOptions::new().valid_set("valid", &valid);
+    /// let bst = Booster::train_with_options(train, &params, options).unwrap();
+    /// let history = bst.eval_history();
+    /// assert_eq!(history.metrics("valid"), vec!["auc", "binary_logloss"]);
+    /// ```
+    pub fn train_with_options<P: ToParams + ?Sized>(
+        dataset: Dataset,
+        parameter: &P,
+        mut options: TrainOptions,
+    ) -> Result<Self> {
+        let parameter = parameter.to_params()?;
+        let num_iterations = parameter.num_iterations.unwrap_or(100);
+        let mut early_stopping = EarlyStopping::from_params(&parameter)?;
 
-        let mut handle = std::ptr::null_mut();
-        lgbm_call!(lightgbm_sys::LGBM_BoosterCreate(
-            dataset.handle,
-            params_cstring.as_ptr() as *const c_char,
-            &mut handle
-        ))?;
+        let mut trainer = Trainer::new(
+            &dataset,
+            parameter,
+            &options.valid_sets,
+            options.train_name.take(),
+            options.objective.take(),
+            std::mem::take(&mut options.metrics),
+        )?;
+        if early_stopping.is_some() && (options.valid_sets.is_empty() || !trainer.has_metrics()) {
+            return Err(Error::new(
+                "early stopping requires at least one validation dataset and metric",
+            ));
+        }
+
+        let mut stopped = None;
+        'train: for iteration in 1..=num_iterations {
+            for callback in options.callbacks.iter_mut() {
+                if callback.before_iteration(&mut trainer.booster, iteration)?
+                    == CallbackAction::Stop
+                {
+                    break 'train;
+                }
+            }
+
+            if trainer.update()? {
+                break;
+            }
+            let results = trainer.eval()?;
+
+            for callback in options.callbacks.iter_mut() {
+                if callback.after_iteration(&mut trainer.booster, iteration, &results)?
+                    == CallbackAction::Stop
+                {
+                    break 'train;
+                }
+            }
 
-        let mut is_finished: i32 = 0;
-        for _ in 1..num_iterations {
-            lgbm_call!(lightgbm_sys::LGBM_BoosterUpdateOneIter(
-                handle,
-                &mut is_finished
-            ))?;
+            if let Some(early_stopping) = early_stopping.as_mut() {
+                let scores = trainer
+                    .valid_results(&results)
+                    .iter()
+                    .map(|r| (r.metric.as_str(), r.value, r.higher_better))
+                    .collect::<Vec<_>>();
+                stopped = early_stopping.update(iteration, &scores);
+                if stopped.is_some() {
+                    break;
+                }
+            }
         }
-        Ok(Booster::new(handle))
+
+        let mut booster = trainer.booster;
+        if let Some(early_stopping) = early_stopping {
+            if let Some((best_iteration, best_score)) = stopped.or_else(|| early_stopping.best()) {
+                booster.best_iteration = Some(best_iteration);
+                booster.best_score = Some(best_score);
+            }
+        }
+        Ok(booster)
+    }
+
+    /// Get the best iteration found by early stopping.
+    ///
+    /// `None` if the model was not trained with `early_stopping_round`.
+    pub fn best_iteration(&self) -> Option<i32> {
+        self.best_iteration
+    }
+
+    /// Get the validation score at [`Booster::best_iteration`] of the metric
+    /// used to decide early stopping.
+    pub fn best_score(&self) -> Option<f64> {
+        self.best_score
+    }
+
+    /// Get the metric values recorded on the evaluated datasets during training.
+    ///
+    /// Empty for models loaded from file or trained without evaluation datasets.
+    pub fn eval_history(&self) -> &EvalHistory {
+        &self.eval_history
+    }
+
+    /// Get names of the metrics reported by `eval`.
+    pub(crate) fn eval_names(&self) -> Result<Vec<String>> {
+        let mut num_eval = 0;
+        lgbm_call!(lightgbm_sys::LGBM_BoosterGetEvalCounts(
+            self.handle,
+            &mut num_eval
+        ))?;
+        let handle = self.handle;
+        read_strings(
+            num_eval as usize,
+            |len, out_len, buffer_len, out_buffer_len, out_strs| unsafe {
+                lightgbm_sys::LGBM_BoosterGetEvalNames(
+                    handle,
+                    len,
+                    out_len,
+                    buffer_len,
+                    out_buffer_len,
+                    out_strs,
+                )
+            },
+        )
+    }
+
+    /// Get the current raw or transformed predictions on the dataset at `data_idx`.
+    pub(crate) fn inner_predict(&self, data_idx: i32) -> Result<Vec<f64>> {
+        let mut num_predict: c_longlong = 0;
+        lgbm_call!(lightgbm_sys::LGBM_BoosterGetNumPredict(
+            self.handle,
+            data_idx,
+            &mut num_predict
+        ))?;
+
+        let mut out_len: c_longlong = 0;
+        let mut out_result: Vec<f64> = vec![Default::default(); num_predict as usize];
+        lgbm_call!(lightgbm_sys::LGBM_BoosterGetPredict(
+            self.handle,
+            data_idx,
+            &mut out_len,
+            out_result.as_mut_ptr() as *mut c_double
+        ))?;
+        out_result.truncate(out_len as usize);
+        Ok(out_result)
+    }
+
+    /// Evaluate the current model on the dataset at `data_idx`.
+    pub(crate) fn eval(&self, data_idx: i32, num_eval: usize) -> Result<Vec<f64>> {
+        let mut out_len = 0;
+        let mut out_result: Vec<f64> = vec![Default::default(); num_eval];
+        lgbm_call!(lightgbm_sys::LGBM_BoosterGetEval(
+            self.handle,
+            data_idx,
+            &mut out_len,
+            out_result.as_mut_ptr() as *mut c_double
+        ))?;
+        out_result.truncate(out_len as usize);
+        Ok(out_result)
     }
 
     /// Predict results for given data.
     ///
+    /// Uses the best iteration if the model was trained with early stopping.
+    ///
     /// Input data example
     /// ```
     /// let data = vec![1.0, 0.1, 0.2,
@@ -105,39 +280,387 @@ impl Booster {
     /// let output = vec![1.0, 0.109, 0.433];
     /// ```
     pub fn predict(&self, data: &[f32], num_features: i32) -> Result<Vec<f64>> {
+        self.predict_with_type(data, num_features, PredictType::Normal)
+            .map(Predictions::into_values)
+    }
+
+    /// Predict raw scores, leaf indices or SHAP feature contributions for
+    /// given data, in row-major order.
+    ///
+    /// Uses the best iteration if the model was trained with early stopping.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::{Booster, PredictType};
+    ///
+    /// let booster = Booster::from_file(&"test/test_from_file.input").unwrap();
+    /// let data = vec![0.5_f32; 28];
+    /// let raw = booster.predict_with_type(&data, 28, PredictType::RawScore).unwrap();
+    /// let leaves = booster.predict_with_type(&data, 28, PredictType::LeafIndex).unwrap();
+    /// assert_eq!(raw.shape(), (1, 1, 1));
+    /// ```
+    pub fn predict_with_type(
+        &self,
+        data: &[f32],
+        num_features: i32,
+        predict_type: PredictType,
+    ) -> Result<Predictions> {
+        let options = PredictOptions::new().predict_type(predict_type);
+        self.predict_with_options(data, num_features, &options)
+    }
+
+    /// Predict for given data with an iteration range and LightGBM prediction
+    /// parameters, see [`PredictOptions`].
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::{Booster, PredictOptions};
+    ///
+    /// let booster = Booster::from_file(&"test/test_from_file.input").unwrap();
+    /// let data = vec![0.5_f32; 28];
+    /// // predictions after 1, 2 and 3 iterations
+    /// let staged = (1..=3)
+    ///     .map(|n| {
+    ///         let options = PredictOptions::new().num_iteration(n);
+    ///         booster.predict_with_options(&data, 28, &options).unwrap()
+    ///     })
+    ///     .collect::<Vec<_>>();
+    /// ```
+    pub fn predict_with_options(
+        &self,
+        data: &[f32],
+        num_features: i32,
+        options: &PredictOptions,
+    ) -> Result<Predictions> {
         let ncol = num_features;
-        let nrow = data.len() as i32 / ncol;
-        let is_row_major = 1 as i32;
-        let start_iteration = 0 as i32;
-        let num_iteration = -1 as i32; // no limit
-        let parameters = CString::new("").unwrap();
+        let nrow = (data.len() as i32).checked_div(ncol).unwrap_or(0);
+        if ncol <= 0 || nrow * ncol != data.len() as i32 {
+            return Err(Error::new(format!(
+                "length of data ({}) is not a multiple of the number of features ({})",
+                data.len(),
+                num_features
+            )));
+        }
+        self.predict_for_mat(data, nrow, ncol, options)
+    }
 
-        // get num_class
-        let mut num_class = 0;
-        lgbm_call!(lightgbm_sys::LGBM_BoosterGetNumClasses(
+    /// Predict for rows of features, with the values of every class of a row
+    /// in [`Predictions::row`].
+    ///
+    /// Example
+    /// ```
+    /// extern crate serde_json;
+    /// use lightgbm::{Booster, Dataset};
+    /// use serde_json::json;
+    ///
+    /// let data = vec![vec![1.0, 0.1, 0.2],
+    ///                 vec![0.7, 0.4, 0.5],
+    ///                 vec![0.9, 0.8, 0.5],
+    ///                 vec![0.2, 0.2, 0.8],
+    ///                 vec![0.1, 0.7, 1.0],
+    ///                 vec![0.3, 0.9, 0.4]];
+    /// let label = vec![0.0, 0.0, 1.0, 1.0, 2.0, 2.0];
+    /// let dataset = Dataset::from_mat(data.clone(), label).unwrap();
+    /// let params = json!{
+    ///    {
+    ///         "num_iterations": 5,
+    ///         "objective": "multiclass",
+    ///         "num_class": 3,
+    ///         "min_data_in_leaf": 1
+    ///     }
+    /// };
+    /// let booster = Booster::train(dataset, &params).unwrap();
+    /// let predictions = booster.predict_mat(&data).unwrap();
+    /// assert_eq!(predictions.row(0).len(), 3);
+    /// let classes = predictions.argmax();
+    /// let probabilities: Vec<Vec<f64>> = predictions.to_rows();
+    /// ```
+    pub fn predict_mat(&self, data: &[Vec<f64>]) -> Result<Predictions> {
+        let ncol = data.first().map_or(0, |row| row.len());
+        if let Some(row) = data.iter().position(|row| row.len() != ncol) {
+            return Err(Error::new(format!(
+                "row {} has {} features, expected {}",
+                row,
+                data[row].len(),
+                ncol
+            )));
+        }
+        let flat_data = data.iter().flatten().cloned().collect::<Vec<_>>();
+        self.predict_for_mat(
+            &flat_data,
+            data.len() as i32,
+            ncol as i32,
+            &PredictOptions::new(),
+        )
+    }
+
+    /// Predict for a sparse matrix in CSR (compressed sparse row) format,
+    /// without converting it to a dense matrix.
+    ///
+    /// Row `i` holds the values `data[indptr[i]..indptr[i + 1]]` in the columns
+    /// `indices[indptr[i]..indptr[i + 1]]`, omitted entries are zero.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::{Booster, PredictOptions};
+    ///
+    /// let booster = Booster::from_file(&"test/test_from_file.input").unwrap();
+    /// // two rows with features {0: 1.0, 5: 0.3} and {27: 0.8}
+    /// let indptr: Vec<i32> = vec![0, 2, 3];
+    /// let indices = vec![0, 5, 27];
+    /// let data: Vec<f32> = vec![1.0, 0.3, 0.8];
+    /// let predictions = booster
+    ///     .predict_csr(&indptr, &indices, &data, 28, &PredictOptions::new())
+    ///     .unwrap();
+    /// assert_eq!(predictions.shape(), (2, 1, 1));
+    /// ```
+    pub fn predict_csr<I: IndexType, T: DataType>(
+        &self,
+        indptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_col: usize,
+        options: &PredictOptions,
+    ) -> Result<Predictions> {
+        check_compressed("indptr", indptr, indices, data, num_col)?;
+        let nrow = indptr.len() as i32 - 1;
+        self.predict_with(
+            nrow,
+            options,
+            |predict_type, start_iteration, num_iteration, parameters, out_len, out_result| unsafe {
+                lightgbm_sys::LGBM_BoosterPredictForCSR(
+                    self.handle,
+                    indptr.as_ptr() as *const c_void,
+                    I::DTYPE,
+                    indices.as_ptr(),
+                    data.as_ptr() as *const c_void,
+                    T::DTYPE,
+                    indptr.len() as i64,
+                    data.len() as i64,
+                    num_col as i64,
+                    predict_type,
+                    start_iteration,
+                    num_iteration,
+                    parameters,
+                    out_len,
+                    out_result,
+                )
+            },
+        )
+    }
+
+    /// Predict for a sparse matrix in CSC (compressed sparse column) format,
+    /// without converting it to a dense matrix.
+    ///
+    /// Column `j` holds the values `data[col_ptr[j]..col_ptr[j + 1]]` in the rows
+    /// `indices[col_ptr[j]..col_ptr[j + 1]]`, omitted entries are zero.
+    pub fn predict_csc<I: IndexType, T: DataType>(
+        &self,
+        col_ptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_row: usize,
+        options: &PredictOptions,
+    ) -> Result<Predictions> {
+        check_compressed("col_ptr", col_ptr, indices, data, num_row)?;
+        self.predict_with(
+            num_row as i32,
+            options,
+            |predict_type, start_iteration, num_iteration, parameters, out_len, out_result| unsafe {
+                lightgbm_sys::LGBM_BoosterPredictForCSC(
+                    self.handle,
+                    col_ptr.as_ptr() as *const c_void,
+                    I::DTYPE,
+                    indices.as_ptr(),
+                    data.as_ptr() as *const c_void,
+                    T::DTYPE,
+                    col_ptr.len() as i64,
+                    data.len() as i64,
+                    num_row as i64,
+                    predict_type,
+                    start_iteration,
+                    num_iteration,
+                    parameters,
+                    out_len,
+                    out_result,
+                )
+            },
+        )
+    }
+
+    /// Predict SHAP feature contributions for a sparse matrix in CSR format,
+    /// returned as sparse CSR matrices.
+    ///
+    /// The predict type of `options` is ignored. See [`SparseContributions`] for an example.
+    pub fn predict_contrib_csr<I: IndexType, T: DataType>(
+        &self,
+        indptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_col: usize,
+        options: &PredictOptions,
+    ) -> Result<SparseContributions<I, T>> {
+        check_compressed("indptr", indptr, indices, data, num_col)?;
+        let num_row = indptr.len() - 1;
+        self.predict_sparse_output(
+            (indptr, indices, data),
+            num_col,
+            lightgbm_sys::C_API_MATRIX_TYPE_CSR,
+            options,
+            indptr.len(),
+            (num_row, num_col + 1),
+        )
+    }
+
+    /// Predict SHAP feature contributions for a sparse matrix in CSC format,
+    /// returned as sparse CSC matrices.
+    ///
+    /// The predict type of `options` is ignored.
+    pub fn predict_contrib_csc<I: IndexType, T: DataType>(
+        &self,
+        col_ptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_row: usize,
+        options: &PredictOptions,
+    ) -> Result<SparseContributions<I, T>> {
+        check_compressed("col_ptr", col_ptr, indices, data, num_row)?;
+        let num_col = col_ptr.len() - 1;
+        // the output has an extra column for the expected value
+        self.predict_sparse_output(
+            (col_ptr, indices, data),
+            num_row,
+            lightgbm_sys::C_API_MATRIX_TYPE_CSC,
+            options,
+            col_ptr.len() + 1,
+            (num_row, num_col + 1),
+        )
+    }
+
+    fn predict_sparse_output<I: IndexType, T: DataType>(
+        &self,
+        (ptr, indices, data): (&[I], &[i32], &[T]),
+        num_col_or_row: usize,
+        matrix_type: u32,
+        options: &PredictOptions,
+        class_indptr_len: usize,
+        shape: (usize, usize),
+    ) -> Result<SparseContributions<I, T>> {
+        let (start_iteration, num_iteration) = options.iteration_range(self.best_iteration)?;
+        let parameters = CString::new(options.param_string()?).unwrap();
+        let mut out_len = [0_i64; 2];
+        let mut out_indptr = std::ptr::null_mut();
+        let mut out_indices = std::ptr::null_mut();
+        let mut out_data = std::ptr::null_mut();
+
+        lgbm_call!(lightgbm_sys::LGBM_BoosterPredictSparseOutput(
             self.handle,
-            &mut num_class
+            ptr.as_ptr() as *const c_void,
+            I::DTYPE,
+            indices.as_ptr(),
+            data.as_ptr() as *const c_void,
+            T::DTYPE,
+            ptr.len() as i64,
+            data.len() as i64,
+            num_col_or_row as i64,
+            PredictType::Contrib.c_api(),
+            start_iteration,
+            num_iteration,
+            parameters.as_ptr() as *const c_char,
+            matrix_type as i32,
+            out_len.as_mut_ptr(),
+            &mut out_indptr,
+            &mut out_indices,
+            &mut out_data
         ))?;
 
-        let mut out_length: c_longlong = 0;
-        let out_result: Vec<f64> = vec![Default::default(); (nrow * num_class) as usize];
+        unsafe {
+            SparseContributions::from_raw(
+                out_indptr,
+                out_indices,
+                out_data,
+                out_len,
+                class_indptr_len,
+                shape,
+            )
+        }
+    }
+
+    /// Predict for a dense matrix of `nrow` rows and `ncol` columns in row-major order.
+    fn predict_for_mat<T: DataType>(
+        &self,
+        data: &[T],
+        nrow: i32,
+        ncol: i32,
+        options: &PredictOptions,
+    ) -> Result<Predictions> {
+        let is_row_major = 1 as i32;
+        self.predict_with(
+            nrow,
+            options,
+            |predict_type, start_iteration, num_iteration, parameters, out_len, out_result| unsafe {
+                lightgbm_sys::LGBM_BoosterPredictForMat(
+                    self.handle,
+                    data.as_ptr() as *const c_void,
+                    T::DTYPE,
+                    nrow,
+                    ncol,
+                    is_row_major,
+                    predict_type,
+                    start_iteration,
+                    num_iteration,
+                    parameters,
+                    out_len,
+                    out_result,
+                )
+            },
+        )
+    }
 
-        lgbm_call!(lightgbm_sys::LGBM_BoosterPredictForMat(
+    /// Predict `nrow` rows with a LightGBM predict function taking the
+    /// `(predict_type, start_iteration, num_iteration, parameter, out_len, out_result)`
+    /// arguments, into a buffer sized by `LGBM_BoosterCalcNumPredict`.
+    fn predict_with<F>(
+        &self,
+        nrow: i32,
+        options: &PredictOptions,
+        predict: F,
+    ) -> Result<Predictions>
+    where
+        F: FnOnce(i32, i32, i32, *const c_char, *mut c_longlong, *mut c_double) -> i32,
+    {
+        let predict_type = options.predict_type;
+        let (start_iteration, num_iteration) = options.iteration_range(self.best_iteration)?;
+        let parameters = CString::new(options.param_string()?).unwrap();
+
+        let mut num_predict: c_longlong = 0;
+        lgbm_call!(lightgbm_sys::LGBM_BoosterCalcNumPredict(
             self.handle,
-            data.as_ptr() as *const c_void,
-            lightgbm_sys::C_API_DTYPE_FLOAT32 as i32,
             nrow,
-            ncol,
-            is_row_major,
-            lightgbm_sys::C_API_PREDICT_NORMAL as i32,
+            predict_type.c_api(),
+            start_iteration,
+            num_iteration,
+            &mut num_predict
+        ))?;
+
+        let mut out_length: c_longlong = 0;
+        let mut out_result: Vec<f64> = vec![Default::default(); num_predict as usize];
+        Error::check_return_value(predict(
+            predict_type.c_api(),
             start_iteration,
             num_iteration,
             parameters.as_ptr() as *const c_char,
             &mut out_length,
-            out_result.as_ptr() as *mut c_double,
+            out_result.as_mut_ptr() as *mut c_double,
         ))?;
+        out_result.truncate(out_length as usize);
 
-        Ok(out_result)
+        Predictions::new(
+            predict_type,
+            out_result,
+            nrow as usize,
+            self.num_class()? as usize,
+        )
     }
 
     /// Get number of classes.
@@ -164,29 +687,20 @@ impl Booster {
     /// Get Feature Names.
     pub fn feature_name(&self) -> Result<Vec<String>> {
         let num_feature = self.num_feature()?;
-        let feature_name_length = 32;
-        let mut num_feature_names = 0;
-        let mut out_buffer_len = 0;
-        let out_strs = (0..num_feature)
-            .map(|_| {
-                CString::new(" ".repeat(feature_name_length))
-                    .unwrap()
-                    .into_raw() as *mut c_char
-            })
-            .collect::<Vec<_>>();
-        lgbm_call!(lightgbm_sys::LGBM_BoosterGetFeatureNames(
-            self.handle,
-            feature_name_length as i32,
-            &mut num_feature_names,
+        let handle = self.handle;
+        read_strings(
             num_feature as usize,
-            &mut out_buffer_len,
-            out_strs.as_ptr() as *mut *mut c_char
-        ))?;
-        let output: Vec<String> = out_strs
-            .into_iter()
-            .map(|s| unsafe { CString::from_raw(s).into_string().unwrap() })
-            .collect();
-        Ok(output)
+            |len, out_len, buffer_len, out_buffer_len, out_strs| unsafe {
+                lightgbm_sys::LGBM_BoosterGetFeatureNames(
+                    handle,
+                    len,
+                    out_len,
+                    buffer_len,
+                    out_buffer_len,
+                    out_strs,
+                )
+            },
+        )
     }
 
     // Get Feature Importance
@@ -203,12 +717,15 @@ impl Booster {
     }
 
     /// Save model to file.
+    ///
+    /// Only the iterations up to the best iteration are saved if the model was
+    /// trained with early stopping.
     pub fn save_file(&self, filename: &str) -> Result<()> {
         let filename_str = CString::new(filename).unwrap();
         lgbm_call!(lightgbm_sys::LGBM_BoosterSaveModel(
             self.handle,
             0_i32,
-            -1_i32,
+            self.best_iteration.unwrap_or(-1),
             0_i32,
             filename_str.as_ptr() as *const c_char
         ))?;
@@ -216,6 +733,42 @@ impl Booster {
     }
 }
 
+/// Read a list of strings from a LightGBM getter using the
+/// `(len, out_len, buffer_len, out_buffer_len, out_strs)` convention,
+/// retrying with larger buffers when a string did not fit.
+pub(crate) fn read_strings<F>(num_strings: usize, mut getter: F) -> Result<Vec<String>>
+where
+    F: FnMut(i32, *mut i32, usize, *mut usize, *mut *mut c_char) -> i32,
+{
+    let mut buffer_len = 64;
+    loop {
+        let mut buffers = vec![vec![0_u8; buffer_len]; num_strings];
+        let mut out_strs = buffers
+            .iter_mut()
+            .map(|b| b.as_mut_ptr() as *mut c_char)
+            .collect::<Vec<_>>();
+        let mut out_len = 0;
+        let mut out_buffer_len = 0;
+        Error::check_return_value(getter(
+            num_strings as i32,
+            &mut out_len,
+            buffer_len,
+            &mut out_buffer_len,
+            out_strs.as_mut_ptr(),
+        ))?;
+        if out_buffer_len > buffer_len {
+            buffer_len = out_buffer_len;
+            continue;
+        }
+        let output = out_strs
+            .iter()
+            .take(out_len as usize)
+            .map(|s| unsafe { CStr::from_ptr(*s) }.to_string_lossy().into_owned())
+            .collect();
+        return Ok(output);
+    }
+}
+
 impl Drop for Booster {
     fn drop(&mut self) {
         lgbm_call!(lightgbm_sys::LGBM_BoosterFree(self.handle)).unwrap();
@@ -225,7 +778,8 @@ impl Drop for Booster {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use serde_json::json;
+    use crate::{EvalResult, Metric, ObjectiveType, TrainingCallback};
+    use serde_json::{json, Value};
     use std::fs;
     use std::path::Path;
 
@@ -233,6 +787,13 @@ mod tests {
         Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train")
     }
 
+    fn _read_test_file(reference: &Dataset) -> Result<Dataset> {
+        Dataset::from_file_with_reference(
+            &"lightgbm-sys/lightgbm/examples/binary_classification/binary.test",
+            reference,
+        )
+    }
+
     fn _train_booster(params: &Value) -> Booster {
         let dataset = _read_train_file().unwrap();
         Booster::train(dataset, &params).unwrap()
@@ -272,6 +833,530 @@ mod tests {
         assert_eq!(result.len(), 2500);
     }
 
+    #[test]
+    fn predict_mat() {
+        let params = json! {
+            {
+                "num_iterations": 10,
+                "objective": "binary",
+                "data_random_seed": 0
+            }
+        };
+        let bst = _train_booster(&params);
+        let rows = (0..3)
+            .map(|r| {
+                (0..28)
+                    .map(|i| ((r + i) % 5) as f64 / 4.0)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let predictions = bst.predict_mat(&rows).unwrap();
+        assert_eq!(predictions.shape(), (3, 1, 1));
+        let flat = rows.iter().flatten().map(|&v| v as f32).collect::<Vec<_>>();
+        assert_eq!(predictions.into_values(), bst.predict(&flat, 28).unwrap());
+
+        let mut ragged = rows.clone();
+        ragged[1].pop();
+        assert!(bst.predict_mat(&ragged).is_err());
+    }
+
+    #[test]
+    fn predict_sparse() {
+        let params = json! {
+            {
+                "num_iterations": 10,
+                "objective": "binary",
+                "data_random_seed": 0
+            }
+        };
+        let bst = _train_booster(&params);
+        // every fourth feature is zero
+        let dense = (0..3 * 28)
+            .map(|i| (i % 4) as f32 / 4.0)
+            .collect::<Vec<_>>();
+        let expected = bst.predict(&dense, 28).unwrap();
+
+        let mut indptr: Vec<i64> = vec![0];
+        let (mut indices, mut data) = (Vec::new(), Vec::new());
+        for row in dense.chunks(28) {
+            for (col, &value) in row.iter().enumerate().filter(|(_, &v)| v != 0.0) {
+                indices.push(col as i32);
+                data.push(value);
+            }
+            indptr.push(data.len() as i64);
+        }
+        let options = PredictOptions::new();
+        let csr = bst
+            .predict_csr(&indptr, &indices, &data, 28, &options)
+            .unwrap();
+        assert_eq!(csr.into_values(), expected);
+
+        let mut col_ptr: Vec<i32> = vec![0];
+        let (mut indices, mut data) = (Vec::new(), Vec::new());
+        for col in 0..28 {
+            for row in 0..3 {
+                let value = dense[row * 28 + col];
+                if value != 0.0 {
+                    indices.push(row as i32);
+                    data.push(f64::from(value));
+                }
+            }
+            col_ptr.push(data.len() as i32);
+        }
+        let csc = bst
+            .predict_csc(&col_ptr, &indices, &data, 3, &options)
+            .unwrap();
+        assert_eq!(csc.into_values(), expected);
+
+        assert!(bst
+            .predict_csr(&[0_i32, 1], &[28], &[1.0_f32], 28, &options)
+            .is_err());
+    }
+
+    #[test]
+    fn predict_contrib_sparse() {
+        let params = json! {
+            {
+                "num_iterations": 10,
+                "objective": "binary",
+                "data_random_seed": 0
+            }
+        };
+        let bst = _train_booster(&params);
+        let dense = (0..3 * 28)
+            .map(|i| (i % 4) as f32 / 4.0)
+            .collect::<Vec<_>>();
+        let expected = bst
+            .predict_with_type(&dense, 28, PredictType::Contrib)
+            .unwrap();
+
+        let mut indptr: Vec<i32> = vec![0];
+        let (mut indices, mut data) = (Vec::new(), Vec::new());
+        for row in dense.chunks(28) {
+            for (col, &value) in row.iter().enumerate().filter(|(_, &v)| v != 0.0) {
+                indices.push(col as i32);
+                data.push(f64::from(value));
+            }
+            indptr.push(data.len() as i32);
+        }
+        let options = PredictOptions::new();
+        let contrib = bst
+            .predict_contrib_csr(&indptr, &indices, &data, 28, &options)
+            .unwrap();
+        assert_eq!(contrib.num_classes(), 1);
+        assert_eq!(contrib.shape(), (3, 29));
+        let mut csr_dense = vec![0.0; 3 * 29];
+        let out_indptr = contrib.indptr(0);
+        for row in 0..3 {
+            for i in out_indptr[row] as usize..out_indptr[row + 1] as usize {
+                csr_dense[row * 29 + contrib.indices(0)[i] as usize] = contrib.values(0)[i];
+            }
+        }
+        for (a, b) in csr_dense.iter().zip(expected.values()) {
+            assert!((a - b).abs() < 1e-9);
+        }
+
+        let col_ptr = (0..=28).map(|c| c * 3).collect::<Vec<i64>>();
+        let indices = (0..28).flat_map(|_| 0..3).collect::<Vec<i32>>();
+        let data = (0..28)
+            .flat_map(|c| {
+                let dense = &dense;
+                (0..3).map(move |r| dense[r * 28 + c])
+            })
+            .collect::<Vec<f32>>();
+        let contrib = bst
+            .predict_contrib_csc(&col_ptr, &indices, &data, 3, &options)
+            .unwrap();
+        assert_eq!(contrib.shape(), (3, 29));
+        assert_eq!(contrib.indptr(0).len(), 30);
+        let out_col_ptr = contrib.indptr(0);
+        for col in 0..29 {
+            for i in out_col_ptr[col] as usize..out_col_ptr[col + 1] as usize {
+                let row = contrib.indices(0)[i] as usize;
+                let value = f64::from(contrib.values(0)[i]);
+                assert!((value - expected.get(row, 0)[col]).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn predict_with_type() {
+        let params = json! {
+            {
+                "num_iterations": 10,
+                "objective": "binary",
+                "data_random_seed": 0
+            }
+        };
+        let bst = _train_booster(&params);
+        let features = (0..3 * 28)
+            .map(|i| (i % 7) as f32 / 7.0)
+            .collect::<Vec<_>>();
+
+        let raw = bst
+            .predict_with_type(&features, 28, PredictType::RawScore)
+            .unwrap();
+        assert_eq!(raw.shape(), (3, 1, 1));
+        let normal = bst.predict(&features, 28).unwrap();
+        for (p, r) in normal.iter().zip(raw.values()) {
+            assert!((p - 1.0 / (1.0 + (-r).exp())).abs() < 1e-9);
+        }
+
+        let leaves = bst
+            .predict_with_type(&features, 28, PredictType::LeafIndex)
+            .unwrap();
+        assert_eq!(leaves.shape().0, 3);
+        assert!(leaves.shape().2 > 0);
+
+        // contributions and the expected value add up to the raw score
+        let contrib = bst
+            .predict_with_type(&features, 28, PredictType::Contrib)
+            .unwrap();
+        assert_eq!(contrib.shape(), (3, 1, 29));
+        for row in 0..3 {
+            let total = contrib.get(row, 0).iter().sum::<f64>();
+            assert!((total - raw.get(row, 0)[0]).abs() < 1e-6);
+        }
+
+        assert!(bst
+            .predict_with_type(&features[1..], 28, PredictType::Normal)
+            .is_err());
+    }
+
+    #[test]
+    fn predict_with_options() {
+        let params = json! {
+            {
+                "num_iterations": 10,
+                "objective": "binary",
+                "data_random_seed": 0
+            }
+        };
+        let bst = _train_booster(&params);
+        let features = (0..3 * 28)
+            .map(|i| (i % 5) as f32 / 5.0)
+            .collect::<Vec<_>>();
+        let raw = |options: PredictOptions| {
+            let options = options.predict_type(PredictType::RawScore);
+            bst.predict_with_options(&features, 28, &options)
+                .unwrap()
+                .into_values()
+        };
+
+        // raw scores of consecutive iteration ranges add up
+        let all = raw(PredictOptions::new());
+        let first = raw(PredictOptions::new().num_iteration(3));
+        let rest = raw(PredictOptions::new().start_iteration(3));
+        for i in 0..3 {
+            assert!((first[i] + rest[i] - all[i]).abs() < 1e-9);
+        }
+        assert_eq!(raw(PredictOptions::new().param("num_threads", 1)), all);
+
+        let unknown = PredictOptions::new().param("pred_early_stops", true);
+        assert!(bst.predict_with_options(&features, 28, &unknown).is_err());
+    }
+
+    #[test]
+    fn train_with_valid_sets() {
+        let params = json! {
+            {
+                "num_iterations": 5,
+                "objective": "binary",
+                "metric": "auc,binary_logloss",
+                "data_random_seed": 0
+            }
+        };
+        let dataset = _read_train_file().unwrap();
+        let valid = _read_test_file(&dataset).unwrap();
+        let options = TrainOptions::new().valid_set("valid", &valid);
+        let bst = Booster::train_with_options(dataset, &params, options).unwrap();
+
+        let history = bst.eval_history();
+        assert_eq!(history.datasets(), vec!["valid"]);
+        assert_eq!(history.metrics("valid"), vec!["auc", "binary_logloss"]);
+        assert_eq!(history.get("valid", "auc").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn early_stopping() {
+        let params = json! {
+            {
+                "num_iterations": 200,
+                "learning_rate": 0.5,
+                "objective": "binary",
+                "metric": "binary_logloss",
+                "early_stopping_round": 3,
+                "data_random_seed": 0
+            }
+        };
+        let dataset = _read_train_file().unwrap();
+        let valid = _read_test_file(&dataset).unwrap();
+        let options = TrainOptions::new().valid_set("valid", &valid);
+        let bst = Booster::train_with_options(dataset, &params, options).unwrap();
+
+        let history = bst.eval_history().get("valid", "binary_logloss").unwrap();
+        let best_iteration = bst.best_iteration().unwrap();
+        assert!(history.len() < 200);
+        assert_eq!(history.len() as i32, best_iteration + 3);
+        assert_eq!(bst.best_score(), Some(history[best_iteration as usize - 1]));
+    }
+
+    #[test]
+    fn early_stopping_not_triggered() {
+        let params = json! {
+            {
+                "num_iterations": 10,
+                "learning_rate": 0.05,
+                "objective": "binary",
+                "metric": "binary_logloss",
+                "early_stopping_round": 20,
+                "data_random_seed": 0
+            }
+        };
+        let dataset = _read_train_file().unwrap();
+        let valid = _read_test_file(&dataset).unwrap();
+        let options = TrainOptions::new().valid_set("valid", &valid);
+        let bst = Booster::train_with_options(dataset, &params, options).unwrap();
+
+        // all iterations are trained, and the last one can be the best
+        let history = bst.eval_history().get("valid", "binary_logloss").unwrap();
+        assert_eq!(history.len(), 10);
+        assert_eq!(bst.best_iteration(), Some(10));
+        assert_eq!(bst.best_score(), Some(history[9]));
+    }
+
+    #[test]
+    fn early_stopping_without_valid_sets() {
+        let params = json! {
+            {
+                "num_iterations": 10,
+                "objective": "binary",
+                "early_stopping_round": 3
+            }
+        };
+        let dataset = _read_train_file().unwrap();
+        assert!(Booster::train(dataset, &params).is_err());
+    }
+
+    fn _logloss(preds: &[f64], labels: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        let probs = preds
+            .iter()
+            .map(|p| 1.0 / (1.0 + (-p).exp()))
+            .collect::<Vec<_>>();
+        let grad = probs
+            .iter()
+            .zip(labels)
+            .map(|(p, y)| (p - *y as f64) as f32)
+            .collect();
+        let hess = probs.iter().map(|p| (p * (1.0 - p)) as f32).collect();
+        (grad, hess)
+    }
+
+    #[test]
+    fn custom_objective() {
+        let params = json! {
+            {
+                "num_iterations": 10,
+                "objective": "regression",
+                "metric": "auc",
+                "data_random_seed": 0
+            }
+        };
+        let dataset = _read_train_file().unwrap();
+        let valid = _read_test_file(&dataset).unwrap();
+        let options = TrainOptions::new()
+            .valid_set("valid", &valid)
+            .objective(_logloss);
+        let bst = Booster::train_with_options(dataset, &params, options).unwrap();
+
+        let auc = bst.eval_history().get("valid", "auc").unwrap();
+        assert!(auc[auc.len() - 1] > 0.7);
+    }
+
+    #[test]
+    fn custom_objective_wrong_length() {
+        let params = json! {
+            {
+                "num_iterations": 3
+            }
+        };
+        let dataset = _read_train_file().unwrap();
+        let options = TrainOptions::new().objective(|_: &[f64], _: &[f32]| (vec![0.0], vec![1.0]));
+        assert!(Booster::train_with_options(dataset, &params, options).is_err());
+    }
+
+    struct _Accuracy;
+
+    impl Metric for _Accuracy {
+        fn name(&self) -> &str {
+            "accuracy"
+        }
+
+        fn eval(&self, labels: &[f32], _weights: Option<&[f32]>, predictions: &[f64]) -> f64 {
+            let correct = labels
+                .iter()
+                .zip(predictions)
+                .filter(|(y, p)| (**p > 0.5) == (**y > 0.5))
+                .count();
+            correct as f64 / labels.len() as f64
+        }
+
+        fn is_higher_better(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn custom_metric() {
+        let params = json! {
+            {
+                "num_iterations": 5,
+                "objective": "binary",
+                "metric": "auc",
+                "data_random_seed": 0
+            }
+        };
+        let dataset = _read_train_file().unwrap();
+        let valid = _read_test_file(&dataset).unwrap();
+        let options = TrainOptions::new()
+            .valid_set("valid", &valid)
+            .eval_train("training")
+            .metric(_Accuracy);
+        let bst = Booster::train_with_options(dataset, &params, options).unwrap();
+
+        let history = bst.eval_history();
+        assert_eq!(history.datasets(), vec!["training", "valid"]);
+        assert_eq!(history.metrics("training"), vec!["auc", "accuracy"]);
+        assert_eq!(history.metrics("valid"), vec!["auc", "accuracy"]);
+        let accuracy = history.get("valid", "accuracy").unwrap();
+        assert_eq!(accuracy.len(), 5);
+        assert!(accuracy.iter().all(|a| *a > 0.5 && *a <= 1.0));
+    }
+
+    #[test]
+    fn custom_metric_early_stopping() {
+        let params = json! {
+            {
+                "num_iterations": 100,
+                "objective": "binary",
+                "metric": "None",
+                "early_stopping_round": 1,
+                "data_random_seed": 0
+            }
+        };
+        let dataset = _read_train_file().unwrap();
+        let valid = _read_test_file(&dataset).unwrap();
+        let options = TrainOptions::new()
+            .valid_set("valid", &valid)
+            .metric(_Accuracy);
+        let bst = Booster::train_with_options(dataset, &params, options).unwrap();
+
+        let accuracy = bst.eval_history().get("valid", "accuracy").unwrap();
+        let best_iteration = bst.best_iteration().unwrap();
+        assert_eq!(
+            bst.best_score(),
+            Some(accuracy[best_iteration as usize - 1])
+        );
+    }
+
+    #[test]
+    fn callbacks() {
+        struct StopAt(i32);
+
+        impl TrainingCallback for StopAt {
+            fn before_iteration(
+                &mut self,
+                _: &mut Booster,
+                iteration: i32,
+            ) -> Result<CallbackAction> {
+                if iteration == self.0 {
+                    Ok(CallbackAction::Stop)
+                } else {
+                    Ok(CallbackAction::Continue)
+                }
+            }
+        }
+
+        let params = json! {
+            {
+                "num_iterations": 10,
+                "objective": "binary",
+                "metric": "auc",
+                "data_random_seed": 0
+            }
+        };
+        let dataset = _read_train_file().unwrap();
+        let valid = _read_test_file(&dataset).unwrap();
+        let mut seen = Vec::new();
+        {
+            let record = |bst: &mut Booster, iteration: i32, results: &[EvalResult]| {
+                assert_eq!(bst.num_feature().unwrap(), 28);
+                assert_eq!(results.len(), 1);
+                assert_eq!(results[0].dataset, "valid");
+                assert_eq!(results[0].metric, "auc");
+                assert!(results[0].higher_better);
+                seen.push(iteration);
+                Ok(CallbackAction::Continue)
+            };
+            let options = TrainOptions::new()
+                .valid_set("valid", &valid)
+                .callback(record)
+                .callback(StopAt(4));
+            let bst = Booster::train_with_options(dataset, &params, options).unwrap();
+            assert_eq!(bst.eval_history().get("valid", "auc").unwrap().len(), 3);
+        }
+        assert_eq!(seen, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn callback_error() {
+        let params = json! {
+            {
+                "num_iterations": 10,
+                "objective": "binary"
+            }
+        };
+        let dataset = _read_train_file().unwrap();
+        let fail = |_: &mut Booster, _: i32, _: &[EvalResult]| Err(Error::new("cancelled"));
+        let options = TrainOptions::new().callback(fail);
+        let result = Booster::train_with_options(dataset, &params, options);
+        assert_eq!(result.err(), Some(Error::new("cancelled")));
+    }
+
+    #[test]
+    fn train_with_typed_params() {
+        use crate::{MetricType, TrainParamsBuilder};
+        let params = TrainParamsBuilder::default()
+            .objective(ObjectiveType::Binary)
+            .metric(vec![MetricType::Auc])
+            .num_iterations(5)
+            .param("data_random_seed", 0)
+            .build()
+            .unwrap();
+        let dataset = _read_train_file().unwrap();
+        let valid = _read_test_file(&dataset).unwrap();
+        let options = TrainOptions::new().valid_set("valid", &valid);
+        let bst = Booster::train_with_options(dataset, &params, options).unwrap();
+        assert_eq!(bst.eval_history().get("valid", "auc").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn train_with_unknown_params() {
+        let params = json! {
+            {
+                "num_iterations": 5,
+                "objective": "binary",
+                "num_leafs": 31
+            }
+        };
+        let dataset = _read_train_file().unwrap();
+        let result = Booster::train(dataset, &params);
+        assert_eq!(
+            result.err(),
+            Some(Error::new("unknown parameter 'num_leafs'"))
+        );
+    }
+
     #[test]
     fn num_feature() {
         let params = _default_params();
@@ -305,6 +1390,15 @@ mod tests {
         assert_eq!(feature_name, target);
     }
 
+    #[test]
+    fn feature_name_from_dataset() {
+        let mut dataset = _read_train_file().unwrap();
+        let names = (0..28).map(|i| format!("f{}", i)).collect::<Vec<_>>();
+        dataset.set_feature_names(&names).unwrap();
+        let bst = Booster::train(dataset, &_default_params()).unwrap();
+        assert_eq!(bst.feature_name().unwrap(), names);
+    }
+
     #[test]
     fn save_file() {
         let params = _default_params();
@@ -0,0 +1,97 @@
+//! Callbacks invoked by the training loop.
+
+use crate::{Booster, Result};
+
+/// Whether training should go on after a callback returns.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CallbackAction {
+    /// Continue with the next iteration.
+    Continue,
+    /// Stop training, keeping the iterations trained so far.
+    Stop,
+}
+
+/// Value of one metric on one dataset after an iteration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvalResult {
+    /// Name of the evaluated dataset.
+    pub dataset: String,
+    /// Name of the metric.
+    pub metric: String,
+    /// Value of the metric.
+    pub value: f64,
+    /// Whether a larger value is better.
+    pub higher_better: bool,
+}
+
+/// Hooks into [`Booster::train_with_options`](crate::Booster::train_with_options),
+/// e.g. for logging, checkpointing or cancellation.
+///
+/// Iterations are numbered from 1, like [`Booster::best_iteration`](crate::Booster::best_iteration).
+/// Returning an error aborts training with that error.
+///
+/// Closures of the form `FnMut(&mut Booster, i32, &[EvalResult]) -> Result<CallbackAction>`
+/// implement this trait and are called after each iteration.
+///
+/// Example
+/// ```
+/// use std::time::{Duration, Instant};
+/// use lightgbm::{Booster, CallbackAction, EvalResult, Result, TrainOptions, TrainingCallback};
+///
+/// struct Deadline(Instant);
+///
+/// impl TrainingCallback for Deadline {
+///     fn before_iteration(&mut self, _: &mut Booster, _: i32) -> Result<CallbackAction> {
+///         if Instant::now() > self.0 {
+///             Ok(CallbackAction::Stop)
+///         } else {
+///             Ok(CallbackAction::Continue)
+///         }
+///     }
+/// }
+///
+/// let log = |_: &mut Booster, iteration: i32, results: &[EvalResult]| {
+///     for result in results {
+///         println!("[{}] {} {}: {}", iteration, result.dataset, result.metric, result.value);
+///     }
+///     Ok(CallbackAction::Continue)
+/// };
+///
+/// let options = TrainOptions::new()
+///     .callback(Deadline(Instant::now() + Duration::from_secs(60)))
+///     .callback(log);
+/// ```
+pub trait TrainingCallback {
+    /// Called before `iteration` is trained.
+    fn before_iteration(
+        &mut self,
+        _booster: &mut Booster,
+        _iteration: i32,
+    ) -> Result<CallbackAction> {
+        Ok(CallbackAction::Continue)
+    }
+
+    /// Called after `iteration` was trained and evaluated, with the metric values of `iteration`.
+    fn after_iteration(
+        &mut self,
+        _booster: &mut Booster,
+        _iteration: i32,
+        _results: &[EvalResult],
+    ) -> Result<CallbackAction> {
+        Ok(CallbackAction::Continue)
+    }
+}
+
+impl<F> TrainingCallback for F
+where
+    F: FnMut(&mut Booster, i32, &[EvalResult]) -> Result<CallbackAction>,
+{
+    fn after_iteration(
+        &mut self,
+        booster: &mut Booster,
+        iteration: i32,
+        results: &[EvalResult],
+    ) -> Result<CallbackAction> {
+        self(booster, iteration, results)
+    }
+}
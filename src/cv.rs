@@ -0,0 +1,547 @@
+//! Cross-validation of training parameters.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::rng::Rng;
+use crate::train::{EarlyStopping, Trainer};
+use crate::{Booster, Dataset, Error, Result, ToParams, TrainParams};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Strategy {
+    KFold,
+    Stratified,
+    Group(Vec<i32>),
+    Custom(Vec<(Vec<i32>, Vec<i32>)>),
+}
+
+/// How the rows of a dataset are split into cross-validation folds.
+///
+/// Each fold is used once for validation while the other folds are used for training.
+/// Datasets with query groups are always split by query, keeping each query in one fold.
+///
+/// Example
+/// ```
+/// use lightgbm::Folds;
+///
+/// let folds = Folds::stratified(5).shuffle(42);
+/// let folds = Folds::group(2, vec![1, 1, 2, 2, 3, 3, 3]);
+/// // (train rows, validation rows) of each fold
+/// let folds = Folds::custom(vec![(vec![0, 1], vec![2, 3]), (vec![2, 3], vec![0, 1])]);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Folds {
+    strategy: Strategy,
+    num_folds: usize,
+    seed: Option<u64>,
+}
+
+impl Folds {
+    /// Split the rows into `num_folds` folds of consecutive rows.
+    pub fn kfold(num_folds: usize) -> Self {
+        Self::new(Strategy::KFold, num_folds)
+    }
+
+    /// Split the rows into `num_folds` folds, each with about the same
+    /// proportion of every label value as the whole dataset.
+    pub fn stratified(num_folds: usize) -> Self {
+        Self::new(Strategy::Stratified, num_folds)
+    }
+
+    /// Split the rows into `num_folds` folds, keeping all rows with the same
+    /// group id in one fold. `groups` holds the group id of every row.
+    pub fn group(num_folds: usize, groups: Vec<i32>) -> Self {
+        Self::new(Strategy::Group(groups), num_folds)
+    }
+
+    /// Use the given `(train, validation)` row indices of each fold.
+    pub fn custom(folds: Vec<(Vec<i32>, Vec<i32>)>) -> Self {
+        let num_folds = folds.len();
+        Self::new(Strategy::Custom(folds), num_folds)
+    }
+
+    /// Shuffle the rows with `seed` before splitting them into k-fold or stratified folds.
+    pub fn shuffle(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    fn new(strategy: Strategy, num_folds: usize) -> Self {
+        Self {
+            strategy,
+            num_folds,
+            seed: None,
+        }
+    }
+
+    /// Sorted `(train, validation)` row indices of each fold.
+    pub(crate) fn split(&self, dataset: &Dataset) -> Result<Vec<(Vec<i32>, Vec<i32>)>> {
+        let labels = dataset.label()?;
+        let num_data = labels.len();
+        if let Strategy::Custom(folds) = &self.strategy {
+            if folds.is_empty() {
+                return Err(Error::new("at least one fold is required"));
+            }
+            let mut folds = folds.clone();
+            for (train, valid) in folds.iter_mut() {
+                if let Some(index) = train
+                    .iter()
+                    .chain(valid.iter())
+                    .find(|&&i| i < 0 || i as usize >= num_data)
+                {
+                    return Err(Error::new(format!(
+                        "fold index {} is out of bounds for {} rows",
+                        index, num_data
+                    )));
+                }
+                train.sort_unstable();
+                valid.sort_unstable();
+            }
+            return Ok(folds);
+        }
+        if self.num_folds < 2 || self.num_folds > num_data {
+            return Err(Error::new(format!(
+                "number of folds must be between 2 and the number of rows ({}), got {}",
+                num_data, self.num_folds
+            )));
+        }
+
+        let queries = dataset.group()?;
+        let fold_of_row = match (&self.strategy, queries.is_empty()) {
+            (Strategy::Stratified, false) => {
+                return Err(Error::new(
+                    "stratified folds are not supported for datasets with query groups",
+                ))
+            }
+            (Strategy::KFold, false) => {
+                let groups = queries
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(query, &size)| vec![query as i32; size as usize])
+                    .collect::<Vec<_>>();
+                group_folds(&groups, self.num_folds)?
+            }
+            (Strategy::KFold, true) => self.kfold_folds(num_data),
+            (Strategy::Stratified, true) => self.stratified_folds(&labels),
+            (Strategy::Group(groups), _) => {
+                if groups.len() != num_data {
+                    return Err(Error::new(format!(
+                        "length of groups ({}) does not match the number of rows ({})",
+                        groups.len(),
+                        num_data
+                    )));
+                }
+                group_folds(groups, self.num_folds)?
+            }
+            (Strategy::Custom(_), _) => unreachable!(),
+        };
+
+        Ok((0..self.num_folds)
+            .map(|fold| {
+                let (valid, train): (Vec<i32>, Vec<i32>) =
+                    (0..num_data as i32).partition(|&row| fold_of_row[row as usize] == fold);
+                (train, valid)
+            })
+            .collect())
+    }
+
+    fn shuffled(&self, mut rows: Vec<usize>) -> Vec<usize> {
+        if let Some(seed) = self.seed {
+            Rng::new(seed).shuffle(&mut rows);
+        }
+        rows
+    }
+
+    fn kfold_folds(&self, num_data: usize) -> Vec<usize> {
+        let rows = self.shuffled((0..num_data).collect());
+        let mut fold_of_row = vec![0; num_data];
+        let mut start = 0;
+        for fold in 0..self.num_folds {
+            // the first num_data % num_folds folds get one more row
+            let size = num_data / self.num_folds + usize::from(fold < num_data % self.num_folds);
+            for &row in rows[start..start + size].iter() {
+                fold_of_row[row] = fold;
+            }
+            start += size;
+        }
+        fold_of_row
+    }
+
+    fn stratified_folds(&self, labels: &[f32]) -> Vec<usize> {
+        let mut classes: Vec<Vec<usize>> = Vec::new();
+        let mut class_of_label = HashMap::new();
+        for (row, label) in labels.iter().enumerate() {
+            let class = *class_of_label.entry(label.to_bits()).or_insert_with(|| {
+                classes.push(Vec::new());
+                classes.len() - 1
+            });
+            classes[class].push(row);
+        }
+
+        // deal the rows of each class round-robin, continuing where the previous class ended
+        let mut fold_of_row = vec![0; labels.len()];
+        let mut next_fold = 0;
+        for rows in classes {
+            for row in self.shuffled(rows) {
+                fold_of_row[row] = next_fold;
+                next_fold = (next_fold + 1) % self.num_folds;
+            }
+        }
+        fold_of_row
+    }
+}
+
+/// Assign groups to folds, largest group first into the fold with the fewest rows.
+fn group_folds(groups: &[i32], num_folds: usize) -> Result<Vec<usize>> {
+    let mut sizes: Vec<(i32, usize)> = Vec::new();
+    let mut index_of_group = HashMap::new();
+    for &group in groups.iter() {
+        let index = *index_of_group.entry(group).or_insert_with(|| {
+            sizes.push((group, 0));
+            sizes.len() - 1
+        });
+        sizes[index].1 += 1;
+    }
+    if sizes.len() < num_folds {
+        return Err(Error::new(format!(
+            "cannot split {} groups into {} folds",
+            sizes.len(),
+            num_folds
+        )));
+    }
+    sizes.sort_by_key(|&(_, size)| Reverse(size));
+
+    let mut fold_sizes = vec![0; num_folds];
+    let mut fold_of_group = HashMap::new();
+    for (group, size) in sizes {
+        let fold = (0..num_folds).min_by_key(|&f| fold_sizes[f]).unwrap();
+        fold_sizes[fold] += size;
+        fold_of_group.insert(group, fold);
+    }
+    Ok(groups.iter().map(|group| fold_of_group[group]).collect())
+}
+
+/// Per-iteration validation metrics of a cross-validation, aggregated over the folds.
+pub struct CvResult {
+    /// Metric name, mean and standard deviation over the folds of each iteration.
+    metrics: Vec<(String, Vec<f64>, Vec<f64>)>,
+    best_iteration: Option<i32>,
+    boosters: Vec<Booster>,
+}
+
+impl CvResult {
+    /// Get the mean over the folds of `metric`, one value per iteration.
+    pub fn mean(&self, metric: &str) -> Option<&[f64]> {
+        self.metrics
+            .iter()
+            .find(|(name, _, _)| name == metric)
+            .map(|(_, mean, _)| mean.as_slice())
+    }
+
+    /// Get the standard deviation over the folds of `metric`, one value per iteration.
+    pub fn stdv(&self, metric: &str) -> Option<&[f64]> {
+        self.metrics
+            .iter()
+            .find(|(name, _, _)| name == metric)
+            .map(|(_, _, stdv)| stdv.as_slice())
+    }
+
+    /// Get the names of the evaluated metrics.
+    pub fn metrics(&self) -> Vec<&str> {
+        self.metrics
+            .iter()
+            .map(|(name, _, _)| name.as_str())
+            .collect()
+    }
+
+    /// Get the best iteration of the mean metric found by early stopping.
+    ///
+    /// `None` if the parameters do not set `early_stopping_round`.
+    pub fn best_iteration(&self) -> Option<i32> {
+        self.best_iteration
+    }
+
+    /// Get the booster trained on each fold.
+    pub fn boosters(&self) -> &[Booster] {
+        &self.boosters
+    }
+
+    /// Take the booster trained on each fold.
+    pub fn into_boosters(self) -> Vec<Booster> {
+        self.boosters
+    }
+}
+
+/// Cross-validate training parameters on `dataset`.
+///
+/// One booster is trained per fold, all folds in lock-step. After each
+/// iteration the metrics given in the parameters are evaluated on the
+/// validation fold of each booster, and their mean and standard deviation
+/// over the folds are recorded.
+///
+/// If `early_stopping_round` is set, training stops once the mean of no metric
+/// has improved for that many rounds, like [`Booster::train_with_options`].
+/// The results are then truncated to the best iteration, which is also used
+/// by the fold boosters for prediction.
+///
+/// Example
+/// ```
+/// extern crate serde_json;
+/// use lightgbm::{cv, Dataset, Folds};
+/// use serde_json::json;
+///
+/// let dataset = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train").unwrap();
+/// let params = json!{
+///    {
+///         "num_iterations": 10,
+///         "objective": "binary",
+///         "metric": "auc"
+///     }
+/// };
+/// let result = cv(&dataset, &params, &Folds::stratified(3).shuffle(1)).unwrap();
+/// let auc = result.mean("auc").unwrap();
+/// ```
+pub fn cv<P: ToParams + ?Sized>(
+    dataset: &Dataset,
+    parameter: &P,
+    folds: &Folds,
+) -> Result<CvResult> {
+    let parameter = parameter.to_params()?;
+    let fold_data = fold_datasets(dataset, folds)?;
+    cv_on_folds(&fold_data, &parameter)
+}
+
+/// The `(train, validation)` subsets of `dataset` of each fold.
+pub(crate) fn fold_datasets(dataset: &Dataset, folds: &Folds) -> Result<Vec<(Dataset, Dataset)>> {
+    folds
+        .split(dataset)?
+        .iter()
+        .map(|(train, valid)| Ok((dataset.subset(train)?, dataset.subset(valid)?)))
+        .collect()
+}
+
+/// Cross-validate on the already split `(train, validation)` datasets of each fold.
+pub(crate) fn cv_on_folds(
+    fold_data: &[(Dataset, Dataset)],
+    parameter: &TrainParams,
+) -> Result<CvResult> {
+    let num_iterations = parameter.num_iterations.unwrap_or(100);
+    let mut early_stopping = EarlyStopping::from_params(parameter)?;
+
+    let mut trainers = fold_data
+        .iter()
+        .map(|(train, valid)| {
+            let valid_sets = [(String::from("valid"), valid)];
+            Trainer::new(
+                train,
+                parameter.clone(),
+                &valid_sets,
+                None,
+                None,
+                Vec::new(),
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if !trainers[0].has_metrics() {
+        return Err(Error::new("cross-validation requires at least one metric"));
+    }
+
+    let mut metrics: Vec<(String, Vec<f64>, Vec<f64>)> = Vec::new();
+    let mut stopped = None;
+    for iteration in 1..=num_iterations {
+        let mut is_finished = false;
+        for trainer in trainers.iter_mut() {
+            is_finished |= trainer.update()?;
+        }
+        if is_finished {
+            break;
+        }
+
+        let results = trainers
+            .iter_mut()
+            .map(|trainer| trainer.eval())
+            .collect::<Result<Vec<_>>>()?;
+        let mut scores = Vec::new();
+        for (i, result) in results[0].iter().enumerate() {
+            let values = results.iter().map(|r| r[i].value).collect::<Vec<_>>();
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let variance =
+                values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / values.len() as f64;
+            if metrics.len() <= i {
+                metrics.push((result.metric.clone(), Vec::new(), Vec::new()));
+            }
+            metrics[i].1.push(mean);
+            metrics[i].2.push(variance.sqrt());
+            scores.push((result.metric.as_str(), mean, result.higher_better));
+        }
+
+        if let Some(early_stopping) = early_stopping.as_mut() {
+            stopped = early_stopping.update(iteration, &scores);
+            if stopped.is_some() {
+                break;
+            }
+        }
+    }
+
+    let mut best_iteration = None;
+    if let Some(early_stopping) = early_stopping {
+        if let Some((best, _)) = stopped.or_else(|| early_stopping.best()) {
+            best_iteration = Some(best);
+            for (_, mean, stdv) in metrics.iter_mut() {
+                mean.truncate(best as usize);
+                stdv.truncate(best as usize);
+            }
+        }
+    }
+    let boosters = trainers
+        .into_iter()
+        .map(|trainer| {
+            let mut booster = trainer.booster;
+            booster.best_iteration = best_iteration;
+            booster
+        })
+        .collect();
+    Ok(CvResult {
+        metrics,
+        best_iteration,
+        boosters,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn _dataset(labels: Vec<f32>) -> Dataset {
+        let data = labels.iter().map(|&l| vec![l as f64, 1.0]).collect();
+        Dataset::from_mat(data, labels).unwrap()
+    }
+
+    fn _valid_rows(folds: &[(Vec<i32>, Vec<i32>)]) -> Vec<Vec<i32>> {
+        folds.iter().map(|(_, valid)| valid.clone()).collect()
+    }
+
+    #[test]
+    fn kfold() {
+        let dataset = _dataset(vec![0.0; 7]);
+        let folds = Folds::kfold(3).split(&dataset).unwrap();
+        assert_eq!(
+            _valid_rows(&folds),
+            vec![vec![0, 1, 2], vec![3, 4], vec![5, 6]]
+        );
+        assert_eq!(folds[1].0, vec![0, 1, 2, 5, 6]);
+
+        let shuffled = Folds::kfold(3).shuffle(7).split(&dataset).unwrap();
+        assert_ne!(shuffled, folds);
+        let mut rows = _valid_rows(&shuffled).concat();
+        rows.sort();
+        assert_eq!(rows, (0..7).collect::<Vec<_>>());
+
+        assert!(Folds::kfold(1).split(&dataset).is_err());
+        assert!(Folds::kfold(8).split(&dataset).is_err());
+    }
+
+    #[test]
+    fn stratified() {
+        let dataset = _dataset(vec![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0]);
+        let folds = Folds::stratified(3).shuffle(3).split(&dataset).unwrap();
+        let labels = dataset.label().unwrap();
+        for valid in _valid_rows(&folds) {
+            assert_eq!(valid.len(), 3);
+            assert_eq!(
+                valid.iter().filter(|&&i| labels[i as usize] == 1.0).count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn group() {
+        let dataset = _dataset(vec![0.0; 7]);
+        let folds = Folds::group(2, vec![5, 5, 5, 9, 9, 2, 2])
+            .split(&dataset)
+            .unwrap();
+        assert_eq!(_valid_rows(&folds), vec![vec![0, 1, 2], vec![3, 4, 5, 6]]);
+        assert!(Folds::group(2, vec![1; 7]).split(&dataset).is_err());
+        assert!(Folds::group(2, vec![1, 2]).split(&dataset).is_err());
+
+        let mut dataset = dataset;
+        dataset.set_group(&[2, 2, 3]).unwrap();
+        let folds = Folds::kfold(2).split(&dataset).unwrap();
+        assert_eq!(_valid_rows(&folds), vec![vec![4, 5, 6], vec![0, 1, 2, 3]]);
+        assert!(Folds::stratified(2).split(&dataset).is_err());
+    }
+
+    #[test]
+    fn custom() {
+        let dataset = _dataset(vec![0.0; 4]);
+        let folds = Folds::custom(vec![(vec![3, 1], vec![0])])
+            .split(&dataset)
+            .unwrap();
+        assert_eq!(folds, vec![(vec![1, 3], vec![0])]);
+        assert!(Folds::custom(vec![(vec![4], vec![0])])
+            .split(&dataset)
+            .is_err());
+        assert!(Folds::custom(vec![]).split(&dataset).is_err());
+    }
+
+    fn _read_train_file() -> Dataset {
+        Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train")
+            .unwrap()
+    }
+
+    #[test]
+    fn cross_validate() {
+        let dataset = _read_train_file();
+        let params = json! {
+            {
+                "num_iterations": 5,
+                "objective": "binary",
+                "metric": "auc,binary_logloss"
+            }
+        };
+        let result = cv(&dataset, &params, &Folds::kfold(3).shuffle(0)).unwrap();
+        assert_eq!(result.metrics(), vec!["auc", "binary_logloss"]);
+        assert_eq!(result.mean("auc").unwrap().len(), 5);
+        assert!(result
+            .stdv("binary_logloss")
+            .unwrap()
+            .iter()
+            .all(|&v| v >= 0.0));
+        assert_eq!(result.boosters().len(), 3);
+        assert_eq!(result.best_iteration(), None);
+    }
+
+    #[test]
+    fn cross_validate_early_stopping() {
+        let dataset = _read_train_file();
+        let params = json! {
+            {
+                "num_iterations": 200,
+                "learning_rate": 1.0,
+                "objective": "binary",
+                "metric": "binary_logloss",
+                "early_stopping_round": 3
+            }
+        };
+        let result = cv(&dataset, &params, &Folds::stratified(3)).unwrap();
+        let best_iteration = result.best_iteration().unwrap();
+        assert!(best_iteration < 200);
+        assert_eq!(
+            result.mean("binary_logloss").unwrap().len(),
+            best_iteration as usize
+        );
+        assert!(result
+            .into_boosters()
+            .iter()
+            .all(|b| b.best_iteration() == Some(best_iteration)));
+    }
+
+    #[test]
+    fn cross_validate_without_metric() {
+        let dataset = _read_train_file();
+        let params = json! {{"objective": "binary", "metric": "None"}};
+        assert!(cv(&dataset, &params, &Folds::kfold(2)).is_err());
+    }
+}
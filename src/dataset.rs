@@ -2,11 +2,14 @@ use libc::{c_char, c_void};
 use lightgbm_sys;
 use std;
 use std::ffi::CString;
+use std::fs::File;
+use std::io::Read;
 
 #[cfg(feature = "dataframe")]
 use polars::prelude::*;
 
-use crate::{Error, Result};
+use crate::booster::read_strings;
+use crate::{DataType, DatasetParams, Error, IndexType, Result};
 
 /// Dataset used throughout LightGBM for training.
 ///
@@ -39,7 +42,7 @@ pub struct Dataset {
 
 #[link(name = "c")]
 impl Dataset {
-    fn new(handle: lightgbm_sys::DatasetHandle) -> Self {
+    pub(crate) fn new(handle: lightgbm_sys::DatasetHandle) -> Self {
         Self { handle }
     }
 
@@ -59,10 +62,71 @@ impl Dataset {
     /// let dataset = Dataset::from_vec(&data, &label, 4).unwrap();
     /// ```
     pub fn from_vec(data: &[f32], labels: &[f32], num_features: i32) -> Result<Self> {
+        Self::create_from_vec(data, labels, num_features, None, &DatasetParams::default())
+    }
+
+    /// Create a new `Dataset` from a dense array in row-major order, with
+    /// feature names and categorical features given by `params`.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::{Dataset, DatasetParamsBuilder};
+    ///
+    /// let params = DatasetParamsBuilder::default()
+    ///     .categorical_feature(vec![1])
+    ///     .build()
+    ///     .unwrap();
+    /// let data = vec![0.5, 2.0, 0.1, 0.0, 0.9, 1.0];
+    /// let dataset = Dataset::from_vec_with_params(&data, &[0.0, 1.0, 1.0], 2, &params).unwrap();
+    /// ```
+    pub fn from_vec_with_params(
+        data: &[f32],
+        labels: &[f32],
+        num_features: i32,
+        params: &DatasetParams,
+    ) -> Result<Self> {
+        Self::create_from_vec(data, labels, num_features, None, params)
+    }
+
+    /// Create a new `Dataset` from a dense array in row-major order, binned
+    /// like the `reference` dataset.
+    ///
+    /// Use this for validation data, so it is evaluated with the same bin
+    /// boundaries as the training data.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// let train = Dataset::from_vec(&[1.0, 0.1, 0.7, 0.4, 0.9, 0.8], &[0.0, 0.0, 1.0], 2).unwrap();
+    /// let valid = Dataset::from_vec_with_reference(&[0.2, 0.2, 0.1, 0.7], &[1.0, 0.0], 2, &train).unwrap();
+    /// ```
+    pub fn from_vec_with_reference(
+        data: &[f32],
+        labels: &[f32],
+        num_features: i32,
+        reference: &Dataset,
+    ) -> Result<Self> {
+        Self::create_from_vec(
+            data,
+            labels,
+            num_features,
+            Some(reference),
+            &DatasetParams::default(),
+        )
+    }
+
+    fn create_from_vec(
+        data: &[f32],
+        labels: &[f32],
+        num_features: i32,
+        reference: Option<&Dataset>,
+        params: &DatasetParams,
+    ) -> Result<Self> {
         let nrows = data.len() as i32 / num_features as i32;
         let ncol = num_features;
         let is_row_major = 1 as i32; // row-major
-        let parameters = CString::new("").unwrap();
+        let parameters = CString::new(params.to_param_string()?).unwrap();
         let label_name = CString::new("label").unwrap();
 
         let mut handle = std::ptr::null_mut();
@@ -74,7 +138,7 @@ impl Dataset {
             ncol,
             is_row_major,
             parameters.as_ptr() as *const c_char,
-            std::ptr::null_mut(),
+            reference_handle(reference),
             &mut handle
         ))?;
 
@@ -86,7 +150,7 @@ impl Dataset {
             lightgbm_sys::C_API_DTYPE_FLOAT32 as i32
         ))?;
 
-        Ok(Self::new(handle))
+        Self::new(handle).with_feature_names(params)
     }
 
     /// Create a new `Dataset` from dense array in row-major order.
@@ -104,11 +168,55 @@ impl Dataset {
     /// let dataset = Dataset::from_mat(data, label).unwrap();
     /// ```
     pub fn from_mat(data: Vec<Vec<f64>>, label: Vec<f32>) -> Result<Self> {
+        Self::create_from_mat(data, label, None, &DatasetParams::default())
+    }
+
+    /// Create a new `Dataset` from dense array in row-major order, with
+    /// feature names and categorical features given by `params`.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::{Dataset, DatasetParamsBuilder};
+    ///
+    /// let params = DatasetParamsBuilder::default()
+    ///     .feature_names(vec![String::from("age"), String::from("city"), String::from("income")])
+    ///     .categorical_feature_names(vec![String::from("city")])
+    ///     .build()
+    ///     .unwrap();
+    /// let data = vec![vec![31.0, 2.0, 1.5],
+    ///                 vec![45.0, 0.0, 2.0],
+    ///                 vec![22.0, 1.0, 0.5]];
+    /// let dataset = Dataset::from_mat_with_params(data, vec![0.0, 1.0, 0.0], &params).unwrap();
+    /// assert_eq!(dataset.feature_names().unwrap(), vec!["age", "city", "income"]);
+    /// ```
+    pub fn from_mat_with_params(
+        data: Vec<Vec<f64>>,
+        label: Vec<f32>,
+        params: &DatasetParams,
+    ) -> Result<Self> {
+        Self::create_from_mat(data, label, None, params)
+    }
+
+    /// Create a new `Dataset` from dense array in row-major order, binned
+    /// like the `reference` dataset.
+    pub fn from_mat_with_reference(
+        data: Vec<Vec<f64>>,
+        label: Vec<f32>,
+        reference: &Dataset,
+    ) -> Result<Self> {
+        Self::create_from_mat(data, label, Some(reference), &DatasetParams::default())
+    }
+
+    fn create_from_mat(
+        data: Vec<Vec<f64>>,
+        label: Vec<f32>,
+        reference: Option<&Dataset>,
+        params: &DatasetParams,
+    ) -> Result<Self> {
         let data_length = data.len();
         let feature_length = data[0].len();
-        let params = CString::new("").unwrap();
+        let params_str = CString::new(params.to_param_string()?).unwrap();
         let label_str = CString::new("label").unwrap();
-        let reference = std::ptr::null_mut(); // not use
         let mut handle = std::ptr::null_mut();
         let flat_data = data.into_iter().flatten().collect::<Vec<_>>();
 
@@ -118,8 +226,8 @@ impl Dataset {
             data_length as i32,
             feature_length as i32,
             1_i32,
-            params.as_ptr() as *const c_char,
-            reference,
+            params_str.as_ptr() as *const c_char,
+            reference_handle(reference),
             &mut handle
         ))?;
 
@@ -131,7 +239,211 @@ impl Dataset {
             lightgbm_sys::C_API_DTYPE_FLOAT32 as i32
         ))?;
 
-        Ok(Self::new(handle))
+        Self::new(handle).with_feature_names(params)
+    }
+
+    /// Create a new `Dataset` from a sparse matrix in CSR (compressed sparse row) format.
+    ///
+    /// Row `i` holds the values `data[indptr[i]..indptr[i + 1]]` in the columns
+    /// `indices[indptr[i]..indptr[i + 1]]`, omitted entries are zero.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// // [[1.0, 0.0, 0.2],
+    /// //  [0.0, 0.0, 0.5],
+    /// //  [0.9, 0.8, 0.0]]
+    /// let indptr: Vec<i32> = vec![0, 2, 3, 5];
+    /// let indices = vec![0, 2, 2, 0, 1];
+    /// let data: Vec<f64> = vec![1.0, 0.2, 0.5, 0.9, 0.8];
+    /// let label = vec![0.0, 1.0, 1.0];
+    /// let dataset = Dataset::from_csr(&indptr, &indices, &data, 3, &label).unwrap();
+    /// ```
+    pub fn from_csr<I: IndexType, T: DataType>(
+        indptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_col: usize,
+        label: &[f32],
+    ) -> Result<Self> {
+        Self::create_from_csr(
+            indptr,
+            indices,
+            data,
+            num_col,
+            label,
+            None,
+            &DatasetParams::default(),
+        )
+    }
+
+    /// Create a new `Dataset` from a sparse matrix in CSR format, with
+    /// feature names and categorical features given by `params`.
+    pub fn from_csr_with_params<I: IndexType, T: DataType>(
+        indptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_col: usize,
+        label: &[f32],
+        params: &DatasetParams,
+    ) -> Result<Self> {
+        Self::create_from_csr(indptr, indices, data, num_col, label, None, params)
+    }
+
+    /// Create a new `Dataset` from a sparse matrix in CSR format, binned
+    /// like the `reference` dataset.
+    pub fn from_csr_with_reference<I: IndexType, T: DataType>(
+        indptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_col: usize,
+        label: &[f32],
+        reference: &Dataset,
+    ) -> Result<Self> {
+        Self::create_from_csr(
+            indptr,
+            indices,
+            data,
+            num_col,
+            label,
+            Some(reference),
+            &DatasetParams::default(),
+        )
+    }
+
+    fn create_from_csr<I: IndexType, T: DataType>(
+        indptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_col: usize,
+        label: &[f32],
+        reference: Option<&Dataset>,
+        params: &DatasetParams,
+    ) -> Result<Self> {
+        check_compressed("indptr", indptr, indices, data, num_col)?;
+        let params_str = CString::new(params.to_param_string()?).unwrap();
+        let mut handle = std::ptr::null_mut();
+
+        lgbm_call!(lightgbm_sys::LGBM_DatasetCreateFromCSR(
+            indptr.as_ptr() as *const c_void,
+            I::DTYPE,
+            indices.as_ptr(),
+            data.as_ptr() as *const c_void,
+            T::DTYPE,
+            indptr.len() as i64,
+            data.len() as i64,
+            num_col as i64,
+            params_str.as_ptr() as *const c_char,
+            reference_handle(reference),
+            &mut handle
+        ))?;
+
+        let mut dataset = Self::new(handle);
+        dataset.set_label(label)?;
+        dataset.with_feature_names(params)
+    }
+
+    /// Create a new `Dataset` from a sparse matrix in CSC (compressed sparse column) format.
+    ///
+    /// Column `j` holds the values `data[col_ptr[j]..col_ptr[j + 1]]` in the rows
+    /// `indices[col_ptr[j]..col_ptr[j + 1]]`, omitted entries are zero.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// // [[1.0, 0.0, 0.2],
+    /// //  [0.0, 0.0, 0.5],
+    /// //  [0.9, 0.8, 0.0]]
+    /// let col_ptr: Vec<i64> = vec![0, 2, 3, 5];
+    /// let indices = vec![0, 2, 2, 0, 1];
+    /// let data: Vec<f32> = vec![1.0, 0.9, 0.8, 0.2, 0.5];
+    /// let label = vec![0.0, 1.0, 1.0];
+    /// let dataset = Dataset::from_csc(&col_ptr, &indices, &data, 3, &label).unwrap();
+    /// ```
+    pub fn from_csc<I: IndexType, T: DataType>(
+        col_ptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_row: usize,
+        label: &[f32],
+    ) -> Result<Self> {
+        Self::create_from_csc(
+            col_ptr,
+            indices,
+            data,
+            num_row,
+            label,
+            None,
+            &DatasetParams::default(),
+        )
+    }
+
+    /// Create a new `Dataset` from a sparse matrix in CSC format, with
+    /// feature names and categorical features given by `params`.
+    pub fn from_csc_with_params<I: IndexType, T: DataType>(
+        col_ptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_row: usize,
+        label: &[f32],
+        params: &DatasetParams,
+    ) -> Result<Self> {
+        Self::create_from_csc(col_ptr, indices, data, num_row, label, None, params)
+    }
+
+    /// Create a new `Dataset` from a sparse matrix in CSC format, binned
+    /// like the `reference` dataset.
+    pub fn from_csc_with_reference<I: IndexType, T: DataType>(
+        col_ptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_row: usize,
+        label: &[f32],
+        reference: &Dataset,
+    ) -> Result<Self> {
+        Self::create_from_csc(
+            col_ptr,
+            indices,
+            data,
+            num_row,
+            label,
+            Some(reference),
+            &DatasetParams::default(),
+        )
+    }
+
+    fn create_from_csc<I: IndexType, T: DataType>(
+        col_ptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        num_row: usize,
+        label: &[f32],
+        reference: Option<&Dataset>,
+        params: &DatasetParams,
+    ) -> Result<Self> {
+        check_compressed("col_ptr", col_ptr, indices, data, num_row)?;
+        let params_str = CString::new(params.to_param_string()?).unwrap();
+        let mut handle = std::ptr::null_mut();
+
+        lgbm_call!(lightgbm_sys::LGBM_DatasetCreateFromCSC(
+            col_ptr.as_ptr() as *const c_void,
+            I::DTYPE,
+            indices.as_ptr(),
+            data.as_ptr() as *const c_void,
+            T::DTYPE,
+            col_ptr.len() as i64,
+            data.len() as i64,
+            num_row as i64,
+            params_str.as_ptr() as *const c_char,
+            reference_handle(reference),
+            &mut handle
+        ))?;
+
+        let mut dataset = Self::new(handle);
+        dataset.set_label(label)?;
+        dataset.with_feature_names(params)
     }
 
     /// Create a new `Dataset` from file.
@@ -154,20 +466,166 @@ impl Dataset {
     /// let dataset = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train");
     /// ```
     pub fn from_file(file_path: &str) -> Result<Self> {
+        Self::create_from_file(file_path, None, &DatasetParams::default())
+    }
+
+    /// Create a new `Dataset` from file, with feature names and categorical
+    /// features given by `params`.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::{Dataset, DatasetParamsBuilder};
+    ///
+    /// let params = DatasetParamsBuilder::default()
+    ///     .categorical_feature(vec![0, 3])
+    ///     .build()
+    ///     .unwrap();
+    /// let dataset = Dataset::from_file_with_params(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train", &params).unwrap();
+    /// ```
+    pub fn from_file_with_params(file_path: &str, params: &DatasetParams) -> Result<Self> {
+        Self::create_from_file(file_path, None, params)
+    }
+
+    /// Create a new `Dataset` from file, binned like the `reference` dataset.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// let train = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train").unwrap();
+    /// let valid = Dataset::from_file_with_reference(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.test", &train).unwrap();
+    /// ```
+    pub fn from_file_with_reference(file_path: &str, reference: &Dataset) -> Result<Self> {
+        Self::create_from_file(file_path, Some(reference), &DatasetParams::default())
+    }
+
+    fn create_from_file(
+        file_path: &str,
+        reference: Option<&Dataset>,
+        params: &DatasetParams,
+    ) -> Result<Self> {
         let file_path_str = CString::new(file_path).unwrap();
-        let params = CString::new("").unwrap();
+        let params_str = CString::new(params.to_param_string()?).unwrap();
         let mut handle = std::ptr::null_mut();
 
         lgbm_call!(lightgbm_sys::LGBM_DatasetCreateFromFile(
             file_path_str.as_ptr() as *const c_char,
+            params_str.as_ptr() as *const c_char,
+            reference_handle(reference),
+            &mut handle
+        ))?;
+
+        Self::new(handle).with_feature_names(params)
+    }
+
+    /// Load a `Dataset` saved with [`Dataset::save_binary`].
+    ///
+    /// Binary files hold the constructed bins, labels and other fields, so
+    /// loading them skips parsing the data and constructing the bins.
+    /// [`Dataset::from_file`] loads binary files as well, this method fails if
+    /// the file is not a LightGBM binary dataset instead of parsing it as text.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// let dataset = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train").unwrap();
+    /// dataset.save_binary(&"binary.train.bin").unwrap();
+    /// let dataset = Dataset::from_binary_file(&"binary.train.bin").unwrap();
+    /// # std::fs::remove_file("binary.train.bin").unwrap();
+    /// ```
+    pub fn from_binary_file(file_path: &str) -> Result<Self> {
+        let mut token = vec![0_u8; BINARY_FILE_TOKEN.len()];
+        File::open(file_path)
+            .and_then(|mut file| file.read_exact(&mut token))
+            .map_err(|e| Error::new(format!("cannot read '{}': {}", file_path, e)))?;
+        if token != BINARY_FILE_TOKEN {
+            return Err(Error::new(format!(
+                "'{}' is not a LightGBM binary dataset",
+                file_path
+            )));
+        }
+        Self::from_file(file_path)
+    }
+
+    /// Save the constructed dataset to a LightGBM binary file.
+    ///
+    /// See [`Dataset::from_binary_file`] for an example.
+    pub fn save_binary(&self, file_path: &str) -> Result<()> {
+        let file_path_str = CString::new(file_path).unwrap();
+        lgbm_call!(lightgbm_sys::LGBM_DatasetSaveBinary(
+            self.handle,
+            file_path_str.as_ptr() as *const c_char
+        ))?;
+        Ok(())
+    }
+
+    /// Create a new `Dataset` from the rows at `indices`, reusing the constructed bins.
+    ///
+    /// `indices` must be sorted in increasing order without duplicates. If the
+    /// dataset has query groups, each query must be kept entirely or not at all.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// let dataset = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train").unwrap();
+    /// let even_rows = (0..7000).step_by(2).collect::<Vec<i32>>();
+    /// let subset = dataset.subset(&even_rows).unwrap();
+    /// ```
+    pub fn subset(&self, indices: &[i32]) -> Result<Self> {
+        let num_data = self.num_data()?;
+        if indices.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(Error::new(
+                "subset indices must be sorted in increasing order without duplicates",
+            ));
+        }
+        if let Some(index) = indices.iter().find(|&&i| i < 0 || i >= num_data) {
+            return Err(Error::new(format!(
+                "subset index {} is out of bounds for {} rows",
+                index, num_data
+            )));
+        }
+        let params = CString::new("").unwrap();
+        let mut handle = std::ptr::null_mut();
+
+        lgbm_call!(lightgbm_sys::LGBM_DatasetGetSubset(
+            self.handle,
+            indices.as_ptr(),
+            indices.len() as i32,
             params.as_ptr() as *const c_char,
-            std::ptr::null_mut(),
             &mut handle
         ))?;
 
         Ok(Self::new(handle))
     }
 
+    /// Serialize the bin boundaries and feature schema of the dataset, without the rows.
+    ///
+    /// Use [`DatasetBuilder::from_serialized_reference`](crate::DatasetBuilder::from_serialized_reference)
+    /// to push rows binned identically in another process.
+    pub fn serialize_reference(&self) -> Result<Vec<u8>> {
+        let mut buffer = std::ptr::null_mut();
+        let mut buffer_len = 0;
+        lgbm_call!(lightgbm_sys::LGBM_DatasetSerializeReferenceToBinary(
+            self.handle,
+            &mut buffer,
+            &mut buffer_len
+        ))?;
+
+        // The C API has no accessor for the buffer's data pointer, only
+        // LGBM_ByteBufferGetAt, so copy it byte by byte within buffer_len.
+        let mut bytes = Vec::with_capacity(buffer_len as usize);
+        let copied = (0..buffer_len).try_for_each(|i| {
+            let mut byte = 0;
+            lgbm_call!(lightgbm_sys::LGBM_ByteBufferGetAt(buffer, i, &mut byte))?;
+            bytes.push(byte);
+            Ok(())
+        });
+        lgbm_call!(lightgbm_sys::LGBM_ByteBufferFree(buffer))?;
+        copied.map(|_| bytes)
+    }
+
     /// Create a new `Dataset` from a polars DataFrame.
     ///
     /// Note: the feature ```dataframe``` is required for this method
@@ -218,6 +676,11 @@ impl Dataset {
                 label_values.push(val);
             });
 
+        let feature_names = dataframe
+            .get_column_names()
+            .iter()
+            .map(|name| String::from(*name))
+            .collect::<Vec<_>>();
         let mut feature_values = Vec::with_capacity(m);
         for _i in 0..m {
             feature_values.push(Vec::with_capacity(n));
@@ -235,10 +698,388 @@ impl Dataset {
                 .enumerate()
                 .for_each(|(row_idx, val)| feature_values[row_idx].push(val));
         }
-        Self::from_mat(feature_values, label_values)
+        let params = DatasetParams {
+            feature_names: Some(feature_names),
+            ..Default::default()
+        };
+        Self::from_mat_with_params(feature_values, label_values, &params)
+    }
+
+    /// Set the name of every feature.
+    ///
+    /// The names are saved in models trained on the dataset, and reported by
+    /// [`Booster::feature_name`](crate::Booster::feature_name).
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// let mut dataset = Dataset::from_vec(&[1.0, 0.1, 0.7, 0.4, 0.9, 0.8], &[0.0, 0.0, 1.0], 2).unwrap();
+    /// dataset.set_feature_names(&["height", "weight"]).unwrap();
+    /// assert_eq!(dataset.feature_names().unwrap(), vec!["height", "weight"]);
+    /// ```
+    pub fn set_feature_names<S: AsRef<str>>(&mut self, feature_names: &[S]) -> Result<()> {
+        let num_feature = self.num_feature()?;
+        if feature_names.len() != num_feature as usize {
+            return Err(Error::new(format!(
+                "number of feature names ({}) does not match the number of features ({})",
+                feature_names.len(),
+                num_feature
+            )));
+        }
+        let names = feature_names
+            .iter()
+            .map(|name| {
+                CString::new(name.as_ref())
+                    .map_err(|_| Error::new("feature names cannot contain nul bytes"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut name_ptrs = names.iter().map(|name| name.as_ptr()).collect::<Vec<_>>();
+
+        lgbm_call!(lightgbm_sys::LGBM_DatasetSetFeatureNames(
+            self.handle,
+            name_ptrs.as_mut_ptr(),
+            num_feature
+        ))?;
+        Ok(())
+    }
+
+    /// Get the name of every feature, `Column_<i>` unless set.
+    pub fn feature_names(&self) -> Result<Vec<String>> {
+        let num_feature = self.num_feature()?;
+        let handle = self.handle;
+        read_strings(
+            num_feature as usize,
+            |len, out_len, buffer_len, out_buffer_len, out_strs| unsafe {
+                lightgbm_sys::LGBM_DatasetGetFeatureNames(
+                    handle,
+                    len,
+                    out_len,
+                    buffer_len,
+                    out_buffer_len,
+                    out_strs,
+                )
+            },
+        )
+    }
+
+    /// Set the feature names of `params`, if any, on a newly created dataset.
+    pub(crate) fn with_feature_names(mut self, params: &DatasetParams) -> Result<Self> {
+        if let Some(feature_names) = params.feature_names.as_ref() {
+            self.set_feature_names(feature_names)?;
+        }
+        Ok(self)
+    }
+
+    /// Get the number of rows.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// let dataset = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train").unwrap();
+    /// assert_eq!(dataset.num_data().unwrap(), 7000);
+    /// assert_eq!(dataset.num_feature().unwrap(), 28);
+    /// ```
+    pub fn num_data(&self) -> Result<i32> {
+        let mut num_data = 0;
+        lgbm_call!(lightgbm_sys::LGBM_DatasetGetNumData(
+            self.handle,
+            &mut num_data
+        ))?;
+        Ok(num_data)
+    }
+
+    /// Get the number of features.
+    pub fn num_feature(&self) -> Result<i32> {
+        let mut num_feature = 0;
+        lgbm_call!(lightgbm_sys::LGBM_DatasetGetNumFeature(
+            self.handle,
+            &mut num_feature
+        ))?;
+        Ok(num_feature)
+    }
+
+    /// Get the number of bins of the feature at index `feature`.
+    ///
+    /// Features with a single bin hold one value only and are never used in splits.
+    pub fn feature_num_bin(&self, feature: i32) -> Result<i32> {
+        let num_feature = self.num_feature()?;
+        if feature < 0 || feature >= num_feature {
+            return Err(Error::new(format!(
+                "feature index {} is out of bounds for {} features",
+                feature, num_feature
+            )));
+        }
+        let mut num_bin = 0;
+        lgbm_call!(lightgbm_sys::LGBM_DatasetGetFeatureNumBin(
+            self.handle,
+            feature,
+            &mut num_bin
+        ))?;
+        Ok(num_bin)
+    }
+
+    /// Get the shape, feature names and bins of the dataset.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// let dataset = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train").unwrap();
+    /// let summary = dataset.summary().unwrap();
+    /// let unused = summary.num_bins.iter().filter(|&&n| n <= 1).count();
+    /// println!("{} rows, {} features, {} without splits", summary.num_data, summary.num_feature, unused);
+    /// ```
+    pub fn summary(&self) -> Result<DatasetSummary> {
+        let num_feature = self.num_feature()?;
+        Ok(DatasetSummary {
+            num_data: self.num_data()?,
+            num_feature,
+            feature_names: self.feature_names()?,
+            num_bins: (0..num_feature)
+                .map(|feature| self.feature_num_bin(feature))
+                .collect::<Result<Vec<_>>>()?,
+        })
+    }
+
+    /// Set the label of every row.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// let data = vec![vec![1.0, 0.1], vec![0.7, 0.4], vec![0.9, 0.8]];
+    /// let mut dataset = Dataset::from_mat(data, vec![0.0, 0.0, 0.0]).unwrap();
+    /// dataset.set_label(&[0.0, 1.0, 1.0]).unwrap();
+    /// assert_eq!(dataset.label().unwrap(), vec![0.0, 1.0, 1.0]);
+    /// ```
+    pub fn set_label(&mut self, label: &[f32]) -> Result<()> {
+        self.check_num_data("label", label.len())?;
+        self.set_field("label", label, lightgbm_sys::C_API_DTYPE_FLOAT32)
+    }
+
+    /// Get the label of every row.
+    pub fn label(&self) -> Result<Vec<f32>> {
+        self.get_field("label", lightgbm_sys::C_API_DTYPE_FLOAT32)
+    }
+
+    /// Set the weight of every row, an empty slice removes the weights.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// let data = vec![vec![1.0, 0.1], vec![0.7, 0.4], vec![0.9, 0.8]];
+    /// let mut dataset = Dataset::from_mat(data, vec![0.0, 1.0, 1.0]).unwrap();
+    /// dataset.set_weight(&[1.0, 0.5, 2.0]).unwrap();
+    /// assert_eq!(dataset.weight().unwrap(), vec![1.0, 0.5, 2.0]);
+    /// ```
+    pub fn set_weight(&mut self, weight: &[f32]) -> Result<()> {
+        if !weight.is_empty() {
+            self.check_num_data("weight", weight.len())?;
+        }
+        self.set_field("weight", weight, lightgbm_sys::C_API_DTYPE_FLOAT32)
+    }
+
+    /// Get the weight of every row, empty if no weights were set.
+    pub fn weight(&self) -> Result<Vec<f32>> {
+        self.get_field("weight", lightgbm_sys::C_API_DTYPE_FLOAT32)
+    }
+
+    /// Set the initial score of every row, an empty slice removes the initial scores.
+    ///
+    /// For multiclass models pass one score per row and class, class-major:
+    /// all rows of the first class, then all rows of the second class, and so on.
+    pub fn set_init_score(&mut self, init_score: &[f64]) -> Result<()> {
+        let num_data = self.num_data()? as usize;
+        let num_class = init_score.len().checked_div(num_data).unwrap_or(0);
+        if !init_score.is_empty() && (num_class == 0 || num_class * num_data != init_score.len()) {
+            return Err(Error::new(format!(
+                "length of init_score ({}) must be a multiple of the number of rows ({})",
+                init_score.len(),
+                num_data
+            )));
+        }
+        self.set_field("init_score", init_score, lightgbm_sys::C_API_DTYPE_FLOAT64)
+    }
+
+    /// Get the initial scores, empty if no initial scores were set.
+    pub fn init_score(&self) -> Result<Vec<f64>> {
+        self.get_field("init_score", lightgbm_sys::C_API_DTYPE_FLOAT64)
+    }
+
+    /// Set the query groups used for ranking, an empty slice removes the groups.
+    ///
+    /// `group` holds the number of rows of each query; the rows of a query
+    /// must be contiguous and the sizes must add up to the number of rows.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::Dataset;
+    ///
+    /// let data = vec![vec![1.0, 0.1], vec![0.7, 0.4], vec![0.9, 0.8], vec![0.2, 0.2]];
+    /// let mut dataset = Dataset::from_mat(data, vec![1.0, 0.0, 2.0, 1.0]).unwrap();
+    /// // rows 0 and 1 belong to the first query, rows 2 and 3 to the second
+    /// dataset.set_group(&[2, 2]).unwrap();
+    /// assert_eq!(dataset.group().unwrap(), vec![2, 2]);
+    /// ```
+    pub fn set_group(&mut self, group: &[i32]) -> Result<()> {
+        if !group.is_empty() {
+            if group.iter().any(|&size| size < 0) {
+                return Err(Error::new("group sizes must not be negative"));
+            }
+            let total = group.iter().map(|&size| size as usize).sum();
+            self.check_num_data("group", total)?;
+        }
+        self.set_field("group", group, lightgbm_sys::C_API_DTYPE_INT32)
+    }
+
+    /// Get the number of rows of each query group, empty if no groups were set.
+    pub fn group(&self) -> Result<Vec<i32>> {
+        // LightGBM stores the query boundaries, [0, n1, n1 + n2, ...]
+        let boundaries: Vec<i32> = self.get_field("group", lightgbm_sys::C_API_DTYPE_INT32)?;
+        Ok(boundaries.windows(2).map(|w| w[1] - w[0]).collect())
+    }
+
+    /// Set the position of every row within its query, an empty slice removes the positions.
+    ///
+    /// Positions are used by the unbiased lambdarank objective to model position bias.
+    pub fn set_position(&mut self, position: &[i32]) -> Result<()> {
+        if !position.is_empty() {
+            self.check_num_data("position", position.len())?;
+        }
+        self.set_field("position", position, lightgbm_sys::C_API_DTYPE_INT32)
+    }
+
+    /// Get the position index of every row, empty if no positions were set.
+    ///
+    /// LightGBM renumbers positions in order of first appearance, so this
+    /// returns its internal indices rather than the positions that were set,
+    /// e.g. `[5, 3, 5]` reads back as `[0, 1, 0]`.
+    pub fn position(&self) -> Result<Vec<i32>> {
+        self.get_field("position", lightgbm_sys::C_API_DTYPE_INT32)
+    }
+
+    fn check_num_data(&self, field_name: &str, len: usize) -> Result<()> {
+        let num_data = self.num_data()?;
+        if len != num_data as usize {
+            return Err(Error::new(format!(
+                "length of {} ({}) does not match the number of rows ({})",
+                field_name, len, num_data
+            )));
+        }
+        Ok(())
+    }
+
+    fn set_field<T>(&mut self, field_name: &str, values: &[T], dtype: u32) -> Result<()> {
+        let field_name_str = CString::new(field_name).unwrap();
+        let values_ptr = if values.is_empty() {
+            std::ptr::null()
+        } else {
+            values.as_ptr() as *const c_void
+        };
+
+        lgbm_call!(lightgbm_sys::LGBM_DatasetSetField(
+            self.handle,
+            field_name_str.as_ptr() as *const c_char,
+            values_ptr,
+            values.len() as i32,
+            dtype as i32
+        ))?;
+        Ok(())
+    }
+
+    fn get_field<T: Copy>(&self, field_name: &str, dtype: u32) -> Result<Vec<T>> {
+        let field_name_str = CString::new(field_name).unwrap();
+        let mut out_len = 0;
+        let mut out_ptr = std::ptr::null();
+        let mut out_type = 0;
+
+        lgbm_call!(lightgbm_sys::LGBM_DatasetGetField(
+            self.handle,
+            field_name_str.as_ptr() as *const c_char,
+            &mut out_len,
+            &mut out_ptr,
+            &mut out_type
+        ))?;
+
+        if out_ptr.is_null() || out_len == 0 {
+            return Ok(Vec::new());
+        }
+        if out_type != dtype as i32 {
+            return Err(Error::new(format!(
+                "unexpected data type of field '{}'",
+                field_name
+            )));
+        }
+        let values = unsafe { std::slice::from_raw_parts(out_ptr as *const T, out_len as usize) };
+        Ok(values.to_vec())
     }
 }
 
+/// Shape, feature names and bins of a [`Dataset`], see [`Dataset::summary`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DatasetSummary {
+    /// Number of rows.
+    pub num_data: i32,
+    /// Number of features.
+    pub num_feature: i32,
+    /// Name of every feature.
+    pub feature_names: Vec<String>,
+    /// Number of bins of every feature.
+    pub num_bins: Vec<i32>,
+}
+
+/// Header of LightGBM binary dataset files.
+const BINARY_FILE_TOKEN: &[u8] = b"______LightGBM_Binary_File_Token______\n";
+
+/// Handle of the optional reference dataset, null if there is none.
+fn reference_handle(reference: Option<&Dataset>) -> lightgbm_sys::DatasetHandle {
+    reference.map_or(std::ptr::null_mut(), |r| r.handle)
+}
+
+/// Validate the layout of a compressed sparse matrix, LightGBM reads it unchecked.
+pub(crate) fn check_compressed<I: IndexType, T>(
+    ptr_name: &str,
+    ptr: &[I],
+    indices: &[i32],
+    data: &[T],
+    num_inner: usize,
+) -> Result<()> {
+    if ptr.is_empty() {
+        return Err(Error::new(format!("{} must not be empty", ptr_name)));
+    }
+    if indices.len() != data.len() {
+        return Err(Error::new(format!(
+            "length of indices ({}) does not match the length of data ({})",
+            indices.len(),
+            data.len()
+        )));
+    }
+    let offsets = ptr.iter().map(|&v| v.into()).collect::<Vec<i64>>();
+    if offsets[0] != 0
+        || offsets.windows(2).any(|w| w[0] > w[1])
+        || offsets[offsets.len() - 1] != data.len() as i64
+    {
+        return Err(Error::new(format!(
+            "{} must be non-decreasing from 0 to the length of data ({})",
+            ptr_name,
+            data.len()
+        )));
+    }
+    if let Some(index) = indices
+        .iter()
+        .find(|&&index| index < 0 || index as usize >= num_inner)
+    {
+        return Err(Error::new(format!(
+            "index {} is out of bounds for {} columns or rows",
+            index, num_inner
+        )));
+    }
+    Ok(())
+}
+
 impl Drop for Dataset {
     fn drop(&mut self) {
         lgbm_call!(lightgbm_sys::LGBM_DatasetFree(self.handle)).unwrap();
@@ -284,6 +1125,214 @@ mod tests {
         assert!(dataset.is_ok());
     }
 
+    fn _small_dataset() -> Dataset {
+        let data = vec![
+            vec![1.0, 0.1, 0.2, 0.1],
+            vec![0.7, 0.4, 0.5, 0.1],
+            vec![0.9, 0.8, 0.5, 0.1],
+            vec![0.2, 0.2, 0.8, 0.7],
+            vec![0.1, 0.7, 1.0, 0.9],
+        ];
+        Dataset::from_mat(data, vec![0.0, 0.0, 0.0, 1.0, 1.0]).unwrap()
+    }
+
+    #[test]
+    fn fields() {
+        let mut dataset = _small_dataset();
+        assert_eq!(dataset.label().unwrap(), vec![0.0, 0.0, 0.0, 1.0, 1.0]);
+        assert!(dataset.weight().unwrap().is_empty());
+        assert!(dataset.init_score().unwrap().is_empty());
+        assert!(dataset.group().unwrap().is_empty());
+
+        dataset.set_label(&[1.0, 0.0, 1.0, 0.0, 1.0]).unwrap();
+        assert_eq!(dataset.label().unwrap(), vec![1.0, 0.0, 1.0, 0.0, 1.0]);
+
+        dataset.set_weight(&[1.0, 2.0, 0.5, 1.0, 1.0]).unwrap();
+        assert_eq!(dataset.weight().unwrap(), vec![1.0, 2.0, 0.5, 1.0, 1.0]);
+        dataset.set_weight(&[]).unwrap();
+        assert!(dataset.weight().unwrap().is_empty());
+
+        dataset.set_init_score(&[0.1, 0.2, 0.3, 0.4, 0.5]).unwrap();
+        assert_eq!(dataset.init_score().unwrap(), vec![0.1, 0.2, 0.3, 0.4, 0.5]);
+
+        dataset.set_group(&[2, 3]).unwrap();
+        assert_eq!(dataset.group().unwrap(), vec![2, 3]);
+
+        dataset.set_position(&[0, 1, 0, 1, 2]).unwrap();
+        assert_eq!(dataset.position().unwrap(), vec![0, 1, 0, 1, 2]);
+        dataset.set_position(&[5, 3, 5, 7, 3]).unwrap();
+        assert_eq!(dataset.position().unwrap(), vec![0, 1, 0, 2, 1]);
+    }
+
+    #[test]
+    fn fields_wrong_length() {
+        let mut dataset = _small_dataset();
+        assert!(dataset.set_label(&[1.0, 0.0]).is_err());
+        assert!(dataset.set_weight(&[1.0; 6]).is_err());
+        assert!(dataset.set_init_score(&[0.0; 7]).is_err());
+        assert!(dataset.set_init_score(&[0.0; 10]).is_ok());
+        assert!(dataset.set_group(&[2, 2]).is_err());
+        assert!(dataset.set_group(&[6, -1]).is_err());
+        assert!(dataset.set_position(&[0, 1, 2]).is_err());
+    }
+
+    #[test]
+    fn summary() {
+        let dataset = read_train_file().unwrap();
+        assert_eq!(dataset.num_data().unwrap(), 7000);
+        assert_eq!(dataset.num_feature().unwrap(), 28);
+        assert!(dataset.feature_num_bin(0).unwrap() > 1);
+        assert!(dataset.feature_num_bin(28).is_err());
+        assert!(dataset.feature_num_bin(-1).is_err());
+
+        let summary = dataset.summary().unwrap();
+        assert_eq!(summary.num_data, 7000);
+        assert_eq!(summary.num_feature, 28);
+        assert_eq!(summary.feature_names.len(), 28);
+        assert_eq!(summary.num_bins.len(), 28);
+        assert_eq!(summary.num_bins[0], dataset.feature_num_bin(0).unwrap());
+    }
+
+    #[test]
+    fn feature_names() {
+        let mut dataset = _small_dataset();
+        let default_names = (0..4).map(|i| format!("Column_{}", i)).collect::<Vec<_>>();
+        assert_eq!(dataset.feature_names().unwrap(), default_names);
+
+        dataset.set_feature_names(&["a", "b", "c", "d"]).unwrap();
+        assert_eq!(dataset.feature_names().unwrap(), vec!["a", "b", "c", "d"]);
+        assert!(dataset.set_feature_names(&["a", "b"]).is_err());
+        assert!(dataset.set_feature_names(&["a", "b", "c", "d\0"]).is_err());
+    }
+
+    #[test]
+    fn with_params() {
+        let params = DatasetParams {
+            feature_names: Some(vec![
+                String::from("size"),
+                String::from("color"),
+                String::from("weight"),
+            ]),
+            categorical_feature_names: Some(vec![String::from("color")]),
+            ..Default::default()
+        };
+        let data = vec![
+            vec![1.0, 0.0, 0.2],
+            vec![0.7, 1.0, 0.5],
+            vec![0.9, 2.0, 0.5],
+            vec![0.2, 1.0, 0.8],
+        ];
+        let dataset =
+            Dataset::from_mat_with_params(data, vec![0.0, 1.0, 1.0, 0.0], &params).unwrap();
+        assert_eq!(
+            dataset.feature_names().unwrap(),
+            vec!["size", "color", "weight"]
+        );
+
+        let params = DatasetParams {
+            feature_names: Some(vec![String::from("a")]),
+            ..Default::default()
+        };
+        assert!(
+            Dataset::from_vec_with_params(&[1.0, 0.1, 0.7, 0.4], &[0.0, 1.0], 2, &params).is_err()
+        );
+    }
+
+    #[test]
+    fn subset() {
+        let mut dataset = _small_dataset();
+        dataset.set_weight(&[1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+        let subset = dataset.subset(&[0, 3, 4]).unwrap();
+        assert_eq!(subset.label().unwrap(), vec![0.0, 1.0, 1.0]);
+        assert_eq!(subset.weight().unwrap(), vec![1.0, 4.0, 5.0]);
+
+        assert!(dataset.subset(&[0, 5]).is_err());
+        assert!(dataset.subset(&[-1, 2]).is_err());
+        assert!(dataset.subset(&[3, 1]).is_err());
+        assert!(dataset.subset(&[1, 1]).is_err());
+    }
+
+    #[test]
+    fn save_binary() {
+        let mut dataset = _small_dataset();
+        dataset.set_weight(&[1.0, 2.0, 1.0, 2.0, 1.0]).unwrap();
+        assert_eq!(dataset.save_binary(&"./test/test_save_binary.bin"), Ok(()));
+
+        let loaded = Dataset::from_binary_file(&"./test/test_save_binary.bin").unwrap();
+        assert_eq!(loaded.label().unwrap(), dataset.label().unwrap());
+        assert_eq!(loaded.weight().unwrap(), dataset.weight().unwrap());
+        let _ = std::fs::remove_file("./test/test_save_binary.bin");
+
+        assert!(Dataset::from_binary_file(
+            &"lightgbm-sys/lightgbm/examples/binary_classification/binary.train"
+        )
+        .is_err());
+        assert!(Dataset::from_binary_file(&"./test/missing.bin").is_err());
+    }
+
+    #[test]
+    fn with_reference() {
+        let train = read_train_file().unwrap();
+        let valid = Dataset::from_file_with_reference(
+            &"lightgbm-sys/lightgbm/examples/binary_classification/binary.test",
+            &train,
+        );
+        assert!(valid.is_ok());
+
+        let train = _small_dataset();
+        let data = vec![0.5, 0.3, 0.6, 0.2, 0.8, 0.1, 0.4, 0.4];
+        let label = vec![1.0, 0.0];
+        assert!(Dataset::from_vec_with_reference(&data, &label, 4, &train).is_ok());
+        assert!(Dataset::from_mat_with_reference(
+            vec![data[..4].iter().map(|&v| v as f64).collect()],
+            vec![1.0],
+            &train
+        )
+        .is_ok());
+        let indptr: Vec<i32> = vec![0, 2, 3];
+        let indices = vec![0, 3, 1];
+        let values: Vec<f64> = vec![0.5, 0.2, 0.4];
+        assert!(
+            Dataset::from_csr_with_reference(&indptr, &indices, &values, 4, &label, &train).is_ok()
+        );
+    }
+
+    #[test]
+    fn from_csr() {
+        let indptr = vec![0, 2, 3, 5, 6, 8];
+        let indices = vec![0, 2, 1, 0, 3, 3, 1, 2];
+        let data = vec![1.0, 0.2, 0.4, 0.9, 0.1, 0.7, 0.7, 1.0];
+        let label = vec![0.0, 0.0, 0.0, 1.0, 1.0];
+        let dataset = Dataset::from_csr(&indptr, &indices, &data, 4, &label).unwrap();
+        assert_eq!(dataset.label().unwrap(), label);
+
+        let indptr = indptr.into_iter().map(i64::from).collect::<Vec<_>>();
+        let data = data.into_iter().map(|v| v as f32).collect::<Vec<_>>();
+        assert!(Dataset::from_csr(&indptr, &indices, &data, 4, &label).is_ok());
+    }
+
+    #[test]
+    fn from_csc() {
+        let col_ptr: Vec<i32> = vec![0, 2, 4, 6, 8];
+        let indices = vec![0, 2, 1, 4, 0, 4, 2, 3];
+        let data: Vec<f64> = vec![1.0, 0.9, 0.4, 0.7, 0.2, 1.0, 0.1, 0.7];
+        let label = vec![0.0, 0.0, 0.0, 1.0, 1.0];
+        assert!(Dataset::from_csc(&col_ptr, &indices, &data, 5, &label).is_ok());
+    }
+
+    #[test]
+    fn from_sparse_invalid() {
+        let label = vec![0.0, 1.0];
+        let data: Vec<f64> = vec![1.0, 0.5];
+        assert!(Dataset::from_csr::<i32, _>(&[], &[], &data, 2, &label).is_err());
+        assert!(Dataset::from_csr(&[0, 1, 2], &[0], &data, 2, &label).is_err());
+        assert!(Dataset::from_csr(&[0, 2, 1], &[0, 1], &data, 2, &label).is_err());
+        assert!(Dataset::from_csr(&[0, 1, 3], &[0, 1], &data, 2, &label).is_err());
+        assert!(Dataset::from_csr(&[0, 1, 2], &[0, 2], &data, 2, &label).is_err());
+        assert!(Dataset::from_csc(&[0, 1, 2], &[0, -1], &data, 2, &label).is_err());
+        assert!(Dataset::from_csr(&[0, 1, 2], &[0, 1], &data, 2, &[1.0]).is_err());
+    }
+
     #[cfg(feature = "dataframe")]
     #[test]
     fn from_dataframe() {
@@ -0,0 +1,40 @@
+//! Element types accepted by the LightGBM C API.
+
+use lightgbm_sys;
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+    impl Sealed for i32 {}
+    impl Sealed for i64 {}
+}
+
+/// Floating point type of feature values, `f32` or `f64`.
+pub trait DataType: Copy + Into<f64> + private::Sealed {
+    /// `C_API_DTYPE_*` constant of the type.
+    const DTYPE: i32;
+}
+
+impl DataType for f32 {
+    const DTYPE: i32 = lightgbm_sys::C_API_DTYPE_FLOAT32 as i32;
+}
+
+impl DataType for f64 {
+    const DTYPE: i32 = lightgbm_sys::C_API_DTYPE_FLOAT64 as i32;
+}
+
+/// Integer type of sparse row or column offsets, `i32` or `i64`.
+pub trait IndexType: Copy + Into<i64> + private::Sealed {
+    /// `C_API_DTYPE_*` constant of the type.
+    const DTYPE: i32;
+}
+
+impl IndexType for i32 {
+    const DTYPE: i32 = lightgbm_sys::C_API_DTYPE_INT32 as i32;
+}
+
+impl IndexType for i64 {
+    const DTYPE: i32 = lightgbm_sys::C_API_DTYPE_INT64 as i32;
+}
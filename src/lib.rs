@@ -1,6 +1,8 @@
 extern crate libc;
 extern crate lightgbm_sys;
 extern crate serde_json;
+#[macro_use]
+extern crate derive_builder;
 
 #[cfg(feature = "dataframe")]
 extern crate polars;
@@ -15,8 +17,46 @@ macro_rules! lgbm_call {
 mod error;
 pub use error::{Error, Result};
 
+mod dtype;
+pub use dtype::{DataType, IndexType};
+
 mod dataset;
-pub use dataset::Dataset;
+pub use dataset::{Dataset, DatasetSummary};
+
+mod streaming;
+pub use streaming::{sample_count, sample_indices, DatasetBuilder, RowMetadata};
+
+mod params;
+pub use params::{
+    BoostingType, DatasetParams, DatasetParamsBuilder, MetricType, ObjectiveType, ToParams,
+    TrainParams, TrainParamsBuilder,
+};
+
+mod predict;
+pub use predict::{PredictOptions, PredictType, Predictions, SparseContributions};
 
 mod booster;
 pub use booster::Booster;
+
+mod callback;
+pub use callback::{CallbackAction, EvalResult, TrainingCallback};
+
+mod metric;
+pub use metric::Metric;
+
+mod ranking;
+pub use ranking::{query_group_sizes, RankedRow};
+
+mod objective;
+pub use objective::Objective;
+
+mod train;
+pub use train::{EvalHistory, TrainOptions};
+
+mod cv;
+pub use cv::{cv, CvResult, Folds};
+
+mod tune;
+pub use tune::{SearchSpace, Trial, Tuner};
+
+mod rng;
@@ -0,0 +1,50 @@
+//! Custom evaluation metrics.
+
+/// An evaluation metric implemented in Rust.
+///
+/// Custom metrics are evaluated after every iteration on the same datasets as
+/// the built-in metrics, are recorded in the evaluation history under
+/// [`Metric::name`] and take part in early stopping.
+///
+/// The predictions are the transformed outputs of the model (e.g.
+/// probabilities for `binary`), or raw scores when training with a custom
+/// [`Objective`](crate::Objective). For multiclass models the value for row
+/// `i` and class `k` is at index `k * num_data + i`.
+///
+/// Example
+/// ```
+/// use lightgbm::Metric;
+///
+/// struct Accuracy;
+///
+/// impl Metric for Accuracy {
+///     fn name(&self) -> &str {
+///         "accuracy"
+///     }
+///
+///     fn eval(&self, labels: &[f32], _weights: Option<&[f32]>, predictions: &[f64]) -> f64 {
+///         let correct = labels
+///             .iter()
+///             .zip(predictions)
+///             .filter(|(y, p)| (**p > 0.5) == (**y > 0.5))
+///             .count();
+///         correct as f64 / labels.len() as f64
+///     }
+///
+///     fn is_higher_better(&self) -> bool {
+///         true
+///     }
+/// }
+/// ```
+pub trait Metric {
+    /// Name of the metric in the evaluation history.
+    fn name(&self) -> &str;
+
+    /// Evaluate the `predictions` on a dataset with `labels` and optional `weights`.
+    fn eval(&self, labels: &[f32], weights: Option<&[f32]>, predictions: &[f64]) -> f64;
+
+    /// Whether a larger value is better, used by early stopping.
+    fn is_higher_better(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,49 @@
+//! Custom training objectives.
+
+/// A training objective implemented in Rust.
+///
+/// Given the current raw predictions on the training data and its labels,
+/// returns the first and second order gradients of the loss with respect to
+/// the raw predictions. For multiclass models the predictions, gradients and
+/// hessians are laid out class by class: the value for row `i` and class `k`
+/// is at index `k * num_data + i`.
+///
+/// Closures of the form `FnMut(&[f64], &[f32]) -> (Vec<f32>, Vec<f32>)`
+/// implement this trait.
+///
+/// Example
+/// ```
+/// extern crate serde_json;
+/// use lightgbm::{Dataset, Booster, TrainOptions};
+/// use serde_json::json;
+///
+/// // logistic loss
+/// let logloss = |preds: &[f64], labels: &[f32]| {
+///     let probs: Vec<f64> = preds.iter().map(|p| 1.0 / (1.0 + (-p).exp())).collect();
+///     let grad: Vec<f32> = probs.iter().zip(labels).map(|(p, y)| (p - *y as f64) as f32).collect();
+///     let hess: Vec<f32> = probs.iter().map(|p| (p * (1.0 - p)) as f32).collect();
+///     (grad, hess)
+/// };
+///
+/// let dataset = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train").unwrap();
+/// let params = json!{
+///    {
+///         "num_iterations": 3
+///     }
+/// };
+/// let options = TrainOptions::new().objective(logloss);
+/// let bst = Booster::train_with_options(dataset, &params, options).unwrap();
+/// ```
+pub trait Objective {
+    /// Compute `(gradients, hessians)` for the raw `predictions` and `labels`.
+    fn gradients(&mut self, predictions: &[f64], labels: &[f32]) -> (Vec<f32>, Vec<f32>);
+}
+
+impl<F> Objective for F
+where
+    F: FnMut(&[f64], &[f32]) -> (Vec<f32>, Vec<f32>),
+{
+    fn gradients(&mut self, predictions: &[f64], labels: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        self(predictions, labels)
+    }
+}
@@ -0,0 +1,952 @@
+//! Typed training and dataset parameters.
+
+use libc::c_char;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::CStr;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use lightgbm_sys;
+use serde_json::{Map, Value};
+
+use crate::{Error, Result};
+
+/// Define an enum for a string-valued LightGBM parameter.
+///
+/// The first name of each variant is the one passed to LightGBM, the others
+/// are aliases accepted when parsing.
+macro_rules! param_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident($param:expr) {
+            $($(#[$variant_meta:meta])* $variant:ident => [$($value:expr),+],)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)+
+        }
+
+        impl $name {
+            /// Name of the value in LightGBM parameters.
+            pub fn as_str(&self) -> &'static str {
+                match *self {
+                    $($name::$variant => [$($value),+][0],)+
+                }
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self> {
+                let s = s.trim().to_lowercase();
+                $(if [$($value),+].contains(&s.as_str()) {
+                    return Ok($name::$variant);
+                })+
+                Err(Error::new(format!(
+                    "invalid value '{}' for parameter '{}'",
+                    s, $param
+                )))
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+param_enum! {
+    /// Loss function to optimize, the `objective` parameter.
+    pub enum ObjectiveType("objective") {
+        /// L2 loss.
+        Regression => ["regression", "regression_l2", "l2", "mean_squared_error", "mse", "l2_root", "root_mean_squared_error", "rmse"],
+        /// L1 loss.
+        RegressionL1 => ["regression_l1", "l1", "mean_absolute_error", "mae"],
+        /// Huber loss.
+        Huber => ["huber"],
+        /// Fair loss.
+        Fair => ["fair"],
+        /// Poisson regression.
+        Poisson => ["poisson"],
+        /// Quantile regression.
+        Quantile => ["quantile"],
+        /// MAPE loss.
+        Mape => ["mape", "mean_absolute_percentage_error"],
+        /// Gamma regression with log-link.
+        Gamma => ["gamma"],
+        /// Tweedie regression with log-link.
+        Tweedie => ["tweedie"],
+        /// Binary log loss classification.
+        Binary => ["binary"],
+        /// Softmax multiclass classification.
+        Multiclass => ["multiclass", "softmax"],
+        /// One-vs-all binary multiclass classification.
+        MulticlassOva => ["multiclassova", "multiclass_ova", "ova", "ovr"],
+        /// Cross-entropy with optional linear weights.
+        CrossEntropy => ["cross_entropy", "xentropy"],
+        /// Alternative parameterization of cross-entropy.
+        CrossEntropyLambda => ["cross_entropy_lambda", "xentlambda"],
+        /// LambdaRank ranking.
+        LambdaRank => ["lambdarank"],
+        /// XE_NDCG_MART ranking.
+        RankXendcg => ["rank_xendcg", "xendcg", "xe_ndcg", "xe_ndcg_mart", "xendcg_mart"],
+        /// Gradients are provided by a custom objective.
+        Custom => ["custom", "none", "null", "na"],
+    }
+}
+
+param_enum! {
+    /// Boosting algorithm, the `boosting` parameter.
+    pub enum BoostingType("boosting") {
+        /// Gradient boosting decision trees.
+        Gbdt => ["gbdt", "gbrt"],
+        /// Random forest.
+        Rf => ["rf", "random_forest"],
+        /// Dropouts meet multiple additive regression trees.
+        Dart => ["dart"],
+    }
+}
+
+param_enum! {
+    /// Evaluation metric, an entry of the `metric` parameter.
+    pub enum MetricType("metric") {
+        /// Absolute loss.
+        L1 => ["l1", "mean_absolute_error", "mae", "regression_l1"],
+        /// Square loss.
+        L2 => ["l2", "mean_squared_error", "mse", "regression_l2", "regression"],
+        /// Root square loss.
+        Rmse => ["rmse", "root_mean_squared_error", "l2_root"],
+        /// Quantile regression loss.
+        Quantile => ["quantile"],
+        /// MAPE loss.
+        Mape => ["mape", "mean_absolute_percentage_error"],
+        /// Huber loss.
+        Huber => ["huber"],
+        /// Fair loss.
+        Fair => ["fair"],
+        /// Negative log-likelihood for Poisson regression.
+        Poisson => ["poisson"],
+        /// Negative log-likelihood for Gamma regression.
+        Gamma => ["gamma"],
+        /// Residual deviance for Gamma regression.
+        GammaDeviance => ["gamma_deviance"],
+        /// Negative log-likelihood for Tweedie regression.
+        Tweedie => ["tweedie"],
+        /// NDCG at the `eval_at` positions.
+        Ndcg => ["ndcg", "lambdarank", "rank_xendcg", "xendcg", "xe_ndcg", "xe_ndcg_mart", "xendcg_mart"],
+        /// MAP at the `eval_at` positions.
+        Map => ["map", "mean_average_precision"],
+        /// Area under the ROC curve.
+        Auc => ["auc"],
+        /// Average precision score.
+        AveragePrecision => ["average_precision"],
+        /// Binary log loss.
+        BinaryLogloss => ["binary_logloss", "binary"],
+        /// Binary error rate.
+        BinaryError => ["binary_error"],
+        /// Multiclass AUC.
+        AucMu => ["auc_mu"],
+        /// Multiclass log loss.
+        MultiLogloss => ["multi_logloss", "multiclass", "softmax", "multiclassova", "multiclass_ova", "ova", "ovr"],
+        /// Multiclass error rate.
+        MultiError => ["multi_error"],
+        /// Cross-entropy.
+        CrossEntropy => ["cross_entropy", "xentropy"],
+        /// Intensity-weighted cross-entropy.
+        CrossEntropyLambda => ["cross_entropy_lambda", "xentlambda"],
+        /// Kullback-Leibler divergence.
+        KullbackLeibler => ["kullback_leibler", "kldiv"],
+    }
+}
+
+/// Parameters for training a `Booster`.
+///
+/// Unset parameters keep the LightGBM defaults. Parameters without a typed
+/// setter can be given by name or alias with `param`, and are checked against
+/// the parameters known to LightGBM.
+///
+/// Example
+/// ```
+/// use lightgbm::{MetricType, ObjectiveType, TrainParamsBuilder};
+///
+/// let params = TrainParamsBuilder::default()
+///     .objective(ObjectiveType::Binary)
+///     .metric(vec![MetricType::Auc, MetricType::BinaryLogloss])
+///     .num_iterations(10)
+///     .learning_rate(0.1)
+///     .param("bagging_seed", 3)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Builder, Clone, Debug, Default, PartialEq)]
+#[builder(build_fn(skip), setter(into))]
+pub struct TrainParams {
+    /// Loss function to optimize.
+    pub(crate) objective: Option<ObjectiveType>,
+    /// Boosting algorithm.
+    pub(crate) boosting: Option<BoostingType>,
+    /// Metrics evaluated on the validation data, an empty list disables all metrics.
+    pub(crate) metric: Option<Vec<MetricType>>,
+    /// Number of boosting iterations.
+    pub(crate) num_iterations: Option<i32>,
+    /// Shrinkage rate.
+    pub(crate) learning_rate: Option<f64>,
+    /// Maximum number of leaves in one tree.
+    pub(crate) num_leaves: Option<i32>,
+    /// Maximum depth of a tree, `<= 0` means no limit.
+    pub(crate) max_depth: Option<i32>,
+    /// Minimal number of data in one leaf.
+    pub(crate) min_data_in_leaf: Option<i32>,
+    /// Minimal sum of hessians in one leaf.
+    pub(crate) min_sum_hessian_in_leaf: Option<f64>,
+    /// Fraction of data sampled for bagging.
+    pub(crate) bagging_fraction: Option<f64>,
+    /// Frequency of bagging, `0` disables bagging.
+    pub(crate) bagging_freq: Option<i32>,
+    /// Fraction of features sampled for each tree.
+    pub(crate) feature_fraction: Option<f64>,
+    /// L1 regularization.
+    pub(crate) lambda_l1: Option<f64>,
+    /// L2 regularization.
+    pub(crate) lambda_l2: Option<f64>,
+    /// Minimal gain to perform a split.
+    pub(crate) min_gain_to_split: Option<f64>,
+    /// Maximum number of bins feature values are bucketed in.
+    pub(crate) max_bin: Option<i32>,
+    /// Number of classes for multiclass objectives.
+    pub(crate) num_class: Option<i32>,
+    /// Stop training if no validation metric improved in this many rounds.
+    pub(crate) early_stopping_round: Option<i32>,
+    /// Only check the first metric for early stopping.
+    pub(crate) first_metric_only: Option<bool>,
+    /// Number of threads, `0` means the OpenMP default.
+    pub(crate) num_threads: Option<i32>,
+    /// Seed for the other random seeds.
+    pub(crate) seed: Option<i32>,
+    /// Verbosity of LightGBM logging.
+    pub(crate) verbosity: Option<i32>,
+    /// Gain of each relevance label for ranking, `[0, 1, 3, 7, ...]` by default.
+    pub(crate) label_gain: Option<Vec<f64>>,
+    /// Positions NDCG and MAP are evaluated at for ranking.
+    pub(crate) eval_at: Option<Vec<i32>>,
+    /// Other parameters by canonical name.
+    #[builder(private)]
+    pub(crate) extra: BTreeMap<String, Value>,
+}
+
+impl TrainParamsBuilder {
+    /// Set any LightGBM parameter by name or alias.
+    ///
+    /// Unknown names and invalid values of typed parameters are reported by `build`.
+    pub fn param<V: Into<Value>>(&mut self, key: &str, value: V) -> &mut Self {
+        let mut extra = self.extra.clone().unwrap_or_default();
+        extra.insert(String::from(key), value.into());
+        self.extra(extra)
+    }
+
+    /// Build the parameters, validating those set with `param`.
+    pub fn build(&self) -> Result<TrainParams> {
+        let mut params = TrainParams {
+            objective: self.objective.unwrap_or_default(),
+            boosting: self.boosting.unwrap_or_default(),
+            metric: self.metric.clone().unwrap_or_default(),
+            num_iterations: self.num_iterations.unwrap_or_default(),
+            learning_rate: self.learning_rate.unwrap_or_default(),
+            num_leaves: self.num_leaves.unwrap_or_default(),
+            max_depth: self.max_depth.unwrap_or_default(),
+            min_data_in_leaf: self.min_data_in_leaf.unwrap_or_default(),
+            min_sum_hessian_in_leaf: self.min_sum_hessian_in_leaf.unwrap_or_default(),
+            bagging_fraction: self.bagging_fraction.unwrap_or_default(),
+            bagging_freq: self.bagging_freq.unwrap_or_default(),
+            feature_fraction: self.feature_fraction.unwrap_or_default(),
+            lambda_l1: self.lambda_l1.unwrap_or_default(),
+            lambda_l2: self.lambda_l2.unwrap_or_default(),
+            min_gain_to_split: self.min_gain_to_split.unwrap_or_default(),
+            max_bin: self.max_bin.unwrap_or_default(),
+            num_class: self.num_class.unwrap_or_default(),
+            early_stopping_round: self.early_stopping_round.unwrap_or_default(),
+            first_metric_only: self.first_metric_only.unwrap_or_default(),
+            num_threads: self.num_threads.unwrap_or_default(),
+            seed: self.seed.unwrap_or_default(),
+            verbosity: self.verbosity.unwrap_or_default(),
+            label_gain: self.label_gain.clone().unwrap_or_default(),
+            eval_at: self.eval_at.clone().unwrap_or_default(),
+            extra: BTreeMap::new(),
+        };
+        if let Some(extra) = self.extra.as_ref() {
+            let aliases = param_aliases()?;
+            let mut seen = params
+                .to_json()
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(|k| (k.clone(), k.clone()))
+                .collect();
+            for (key, value) in extra.iter() {
+                params.set_checked(&aliases, &mut seen, key, value)?;
+            }
+        }
+        Ok(params)
+    }
+}
+
+impl TrainParams {
+    /// Parse parameters from a JSON object of LightGBM parameter names or aliases.
+    ///
+    /// Example
+    /// ```
+    /// extern crate serde_json;
+    /// use lightgbm::TrainParams;
+    /// use serde_json::json;
+    ///
+    /// let params = TrainParams::from_json(&json!{
+    ///     {
+    ///         "n_estimators": 10,
+    ///         "objective": "binary",
+    ///         "metric": "auc"
+    ///     }
+    /// }).unwrap();
+    /// assert!(TrainParams::from_json(&json!{{"n_estimator": 10}}).is_err());
+    /// ```
+    pub fn from_json(value: &Value) -> Result<Self> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| Error::new("parameters must be a JSON object"))?;
+        let aliases = param_aliases()?;
+        let mut params = Self::default();
+        let mut seen = HashMap::new();
+        for (key, value) in object.iter() {
+            params.set_checked(&aliases, &mut seen, key, value)?;
+        }
+        Ok(params)
+    }
+
+    /// Set a parameter by name or alias, replacing any previous value.
+    pub fn set<V: Into<Value>>(&mut self, key: &str, value: V) -> Result<()> {
+        let aliases = param_aliases()?;
+        let name = canonical_name(&aliases, key)?;
+        self.set_canonical(&name, &value.into())
+    }
+
+    /// Convert to a JSON object keyed by canonical parameter names.
+    pub fn to_json(&self) -> Value {
+        let mut object = Map::new();
+        {
+            let mut insert = |key: &str, value: Option<Value>| {
+                if let Some(value) = value {
+                    object.insert(String::from(key), value);
+                }
+            };
+            insert("objective", self.objective.map(|v| v.as_str().into()));
+            insert("boosting", self.boosting.map(|v| v.as_str().into()));
+            insert(
+                "metric",
+                self.metric.as_ref().map(|metrics| {
+                    if metrics.is_empty() {
+                        Value::from("None")
+                    } else {
+                        metrics.iter().map(|m| m.as_str()).collect()
+                    }
+                }),
+            );
+            insert("num_iterations", self.num_iterations.map(Value::from));
+            insert("learning_rate", self.learning_rate.map(Value::from));
+            insert("num_leaves", self.num_leaves.map(Value::from));
+            insert("max_depth", self.max_depth.map(Value::from));
+            insert("min_data_in_leaf", self.min_data_in_leaf.map(Value::from));
+            insert(
+                "min_sum_hessian_in_leaf",
+                self.min_sum_hessian_in_leaf.map(Value::from),
+            );
+            insert("bagging_fraction", self.bagging_fraction.map(Value::from));
+            insert("bagging_freq", self.bagging_freq.map(Value::from));
+            insert("feature_fraction", self.feature_fraction.map(Value::from));
+            insert("lambda_l1", self.lambda_l1.map(Value::from));
+            insert("lambda_l2", self.lambda_l2.map(Value::from));
+            insert("min_gain_to_split", self.min_gain_to_split.map(Value::from));
+            insert("max_bin", self.max_bin.map(Value::from));
+            insert("num_class", self.num_class.map(Value::from));
+            insert(
+                "early_stopping_round",
+                self.early_stopping_round.map(Value::from),
+            );
+            insert("first_metric_only", self.first_metric_only.map(Value::from));
+            insert("num_threads", self.num_threads.map(Value::from));
+            insert("seed", self.seed.map(Value::from));
+            insert("verbosity", self.verbosity.map(Value::from));
+            insert("label_gain", self.label_gain.clone().map(Value::from));
+            insert("eval_at", self.eval_at.clone().map(Value::from));
+        }
+        for (key, value) in self.extra.iter() {
+            object.insert(key.clone(), value.clone());
+        }
+        Value::Object(object)
+    }
+
+    /// Format as a LightGBM parameter string, `key1=value1 key2=value2`.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::{MetricType, ObjectiveType, TrainParamsBuilder};
+    ///
+    /// let params = TrainParamsBuilder::default()
+    ///     .objective(ObjectiveType::Binary)
+    ///     .metric(vec![MetricType::Auc, MetricType::BinaryLogloss])
+    ///     .param("is_unbalance", true)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     params.to_param_string().unwrap(),
+    ///     "is_unbalance=true metric=auc,binary_logloss objective=binary"
+    /// );
+    /// ```
+    pub fn to_param_string(&self) -> Result<String> {
+        param_string(self.to_json().as_object().unwrap())
+    }
+
+    /// Set a parameter, failing if it was already given under another name.
+    fn set_checked(
+        &mut self,
+        aliases: &HashMap<String, String>,
+        seen: &mut HashMap<String, String>,
+        key: &str,
+        value: &Value,
+    ) -> Result<()> {
+        let name = canonical_name(aliases, key)?;
+        if let Some(previous) = seen.insert(name.clone(), String::from(key)) {
+            return Err(Error::new(format!(
+                "parameter '{}' is set twice, as '{}' and '{}'",
+                name, previous, key
+            )));
+        }
+        self.set_canonical(&name, value)
+    }
+
+    fn set_canonical(&mut self, name: &str, value: &Value) -> Result<()> {
+        match name {
+            "objective" => self.objective = Some(parse_str(name, value)?.parse()?),
+            "boosting" => self.boosting = Some(parse_str(name, value)?.parse()?),
+            "metric" => self.metric = Some(parse_metrics(value)?),
+            "num_iterations" => self.num_iterations = Some(parse_i32(name, value)?),
+            "learning_rate" => self.learning_rate = Some(parse_f64(name, value)?),
+            "num_leaves" => self.num_leaves = Some(parse_i32(name, value)?),
+            "max_depth" => self.max_depth = Some(parse_i32(name, value)?),
+            "min_data_in_leaf" => self.min_data_in_leaf = Some(parse_i32(name, value)?),
+            "min_sum_hessian_in_leaf" => {
+                self.min_sum_hessian_in_leaf = Some(parse_f64(name, value)?)
+            }
+            "bagging_fraction" => self.bagging_fraction = Some(parse_f64(name, value)?),
+            "bagging_freq" => self.bagging_freq = Some(parse_i32(name, value)?),
+            "feature_fraction" => self.feature_fraction = Some(parse_f64(name, value)?),
+            "lambda_l1" => self.lambda_l1 = Some(parse_f64(name, value)?),
+            "lambda_l2" => self.lambda_l2 = Some(parse_f64(name, value)?),
+            "min_gain_to_split" => self.min_gain_to_split = Some(parse_f64(name, value)?),
+            "max_bin" => self.max_bin = Some(parse_i32(name, value)?),
+            "num_class" => self.num_class = Some(parse_i32(name, value)?),
+            "early_stopping_round" => self.early_stopping_round = Some(parse_i32(name, value)?),
+            "first_metric_only" => self.first_metric_only = Some(parse_bool(name, value)?),
+            "num_threads" => self.num_threads = Some(parse_i32(name, value)?),
+            "seed" => self.seed = Some(parse_i32(name, value)?),
+            "verbosity" => self.verbosity = Some(parse_i32(name, value)?),
+            "label_gain" => self.label_gain = Some(parse_list(name, value, parse_f64)?),
+            "eval_at" => self.eval_at = Some(parse_list(name, value, parse_i32)?),
+            _ => {
+                self.extra.insert(String::from(name), value.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parameters applied when a [`Dataset`](crate::Dataset) is constructed.
+///
+/// Categorical features and the binning parameters can only be set before
+/// the bins are constructed. Categorical features are binned by category
+/// instead of by value, and are given by index, or by name if
+/// `feature_names` is set.
+///
+/// Example
+/// ```
+/// use lightgbm::DatasetParamsBuilder;
+///
+/// let params = DatasetParamsBuilder::default()
+///     .feature_names(vec![String::from("age"), String::from("city")])
+///     .categorical_feature_names(vec![String::from("city")])
+///     .build()
+///     .unwrap();
+/// assert!(DatasetParamsBuilder::default()
+///     .categorical_feature_names(vec![String::from("city")])
+///     .build()
+///     .is_err());
+/// ```
+#[derive(Builder, Clone, Debug, Default, PartialEq)]
+#[builder(build_fn(skip), setter(into))]
+pub struct DatasetParams {
+    /// Name of every feature, `Column_<i>` by default.
+    pub(crate) feature_names: Option<Vec<String>>,
+    /// Indices of the categorical features.
+    pub(crate) categorical_feature: Option<Vec<i32>>,
+    /// Names of the categorical features, looked up in `feature_names`.
+    pub(crate) categorical_feature_names: Option<Vec<String>>,
+    /// Maximum number of bins feature values are bucketed in.
+    pub(crate) max_bin: Option<i32>,
+    /// Number of rows sampled to construct the bins.
+    pub(crate) bin_construct_sample_cnt: Option<i32>,
+    /// Minimal number of rows in a bin.
+    pub(crate) min_data_in_bin: Option<i32>,
+}
+
+impl DatasetParamsBuilder {
+    /// Build the parameters, checking that categorical feature names are known.
+    pub fn build(&self) -> Result<DatasetParams> {
+        let params = DatasetParams {
+            feature_names: self.feature_names.clone().unwrap_or_default(),
+            categorical_feature: self.categorical_feature.clone().unwrap_or_default(),
+            categorical_feature_names: self.categorical_feature_names.clone().unwrap_or_default(),
+            max_bin: self.max_bin.unwrap_or_default(),
+            bin_construct_sample_cnt: self.bin_construct_sample_cnt.unwrap_or_default(),
+            min_data_in_bin: self.min_data_in_bin.unwrap_or_default(),
+        };
+        params.categorical_indices()?;
+        Ok(params)
+    }
+}
+
+impl DatasetParams {
+    /// Sorted indices of all categorical features, by index or by name.
+    fn categorical_indices(&self) -> Result<Vec<i32>> {
+        let mut indices = self.categorical_feature.clone().unwrap_or_default();
+        if let Some(index) = indices.iter().find(|&&i| i < 0) {
+            return Err(Error::new(format!(
+                "categorical feature index {} is negative",
+                index
+            )));
+        }
+        for name in self.categorical_feature_names.iter().flatten() {
+            let index = self
+                .feature_names
+                .iter()
+                .flatten()
+                .position(|feature| feature == name)
+                .ok_or_else(|| {
+                    Error::new(format!(
+                        "categorical feature '{}' is not in the feature names",
+                        name
+                    ))
+                })?;
+            indices.push(index as i32);
+        }
+        indices.sort_unstable();
+        indices.dedup();
+        Ok(indices)
+    }
+
+    /// Format the parameters LightGBM reads at construction as a parameter string.
+    pub(crate) fn to_param_string(&self) -> Result<String> {
+        let mut params = Map::new();
+        let indices = self.categorical_indices()?;
+        if !indices.is_empty() {
+            params.insert(String::from("categorical_feature"), Value::from(indices));
+        }
+        let binning = [
+            ("max_bin", self.max_bin),
+            ("bin_construct_sample_cnt", self.bin_construct_sample_cnt),
+            ("min_data_in_bin", self.min_data_in_bin),
+        ];
+        for (name, value) in binning.iter() {
+            if let Some(value) = value {
+                params.insert(String::from(*name), Value::from(*value));
+            }
+        }
+        param_string(&params)
+    }
+}
+
+/// Types accepted as parameters by [`Booster::train`](crate::Booster::train):
+/// [`TrainParams`] or a JSON object of LightGBM parameters.
+pub trait ToParams {
+    /// Convert to validated training parameters.
+    fn to_params(&self) -> Result<TrainParams>;
+}
+
+impl ToParams for TrainParams {
+    fn to_params(&self) -> Result<TrainParams> {
+        Ok(self.clone())
+    }
+}
+
+impl ToParams for Value {
+    fn to_params(&self) -> Result<TrainParams> {
+        TrainParams::from_json(self)
+    }
+}
+
+impl<T: ToParams + ?Sized> ToParams for &T {
+    fn to_params(&self) -> Result<TrainParams> {
+        (**self).to_params()
+    }
+}
+
+/// Format a JSON object as a LightGBM parameter string.
+///
+/// Strings, numbers and booleans are written as-is, arrays as comma separated
+/// lists, e.g. `{"metric": ["auc", "l2"], "verbose": -1}` becomes `metric=auc,l2 verbose=-1`.
+pub(crate) fn param_string(params: &Map<String, Value>) -> Result<String> {
+    let params = params
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::Array(values) => values
+                    .iter()
+                    .map(|v| param_value(key, v))
+                    .collect::<Result<Vec<_>>>()?
+                    .join(","),
+                v => param_value(key, v)?,
+            };
+            if key.is_empty()
+                || key
+                    .chars()
+                    .chain(value.chars())
+                    .any(|c| c.is_whitespace() || c == '=')
+            {
+                return Err(Error::new(format!(
+                    "parameter '{}' cannot contain whitespace or '='",
+                    key
+                )));
+            }
+            Ok(format!("{}={}", key, value))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(params.join(" "))
+}
+
+/// Format a scalar parameter value.
+fn param_value(key: &str, value: &Value) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(n) => Ok(n.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        _ => Err(Error::new(format!(
+            "parameter '{}' must be a string, number, boolean or a list of them",
+            key
+        ))),
+    }
+}
+
+/// Map every parameter name and alias known to LightGBM to its canonical name.
+pub(crate) fn param_aliases() -> Result<HashMap<String, String>> {
+    let mut buffer_len = 1 << 16;
+    let mut out_len = 0;
+    let mut buffer = Vec::new();
+    while buffer.is_empty() || out_len > buffer_len {
+        buffer_len = std::cmp::max(buffer_len, out_len);
+        buffer = vec![0_u8; buffer_len as usize];
+        lgbm_call!(lightgbm_sys::LGBM_DumpParamAliases(
+            buffer_len,
+            &mut out_len,
+            buffer.as_mut_ptr() as *mut c_char
+        ))?;
+    }
+    let dump = unsafe { CStr::from_ptr(buffer.as_ptr() as *const c_char) };
+    let dump: Value = serde_json::from_str(&dump.to_string_lossy())
+        .map_err(|e| Error::new(format!("cannot parse parameter aliases: {}", e)))?;
+
+    let mut aliases = HashMap::new();
+    for (name, names) in dump.as_object().into_iter().flatten() {
+        aliases.insert(name.clone(), name.clone());
+        for alias in names.as_array().into_iter().flatten() {
+            if let Some(alias) = alias.as_str() {
+                aliases.insert(String::from(alias), name.clone());
+            }
+        }
+    }
+    Ok(aliases)
+}
+
+pub(crate) fn canonical_name(aliases: &HashMap<String, String>, key: &str) -> Result<String> {
+    aliases
+        .get(key.trim())
+        .cloned()
+        .ok_or_else(|| Error::new(format!("unknown parameter '{}'", key)))
+}
+
+fn parse_str<'a>(name: &str, value: &'a Value) -> Result<&'a str> {
+    value
+        .as_str()
+        .ok_or_else(|| Error::new(format!("parameter '{}' must be a string", name)))
+}
+
+fn parse_i32(name: &str, value: &Value) -> Result<i32> {
+    value
+        .as_i64()
+        .filter(|v| *v >= i64::from(i32::MIN) && *v <= i64::from(i32::MAX))
+        .map(|v| v as i32)
+        .ok_or_else(|| Error::new(format!("parameter '{}' must be an integer", name)))
+}
+
+fn parse_f64(name: &str, value: &Value) -> Result<f64> {
+    value
+        .as_f64()
+        .ok_or_else(|| Error::new(format!("parameter '{}' must be a number", name)))
+}
+
+fn parse_bool(name: &str, value: &Value) -> Result<bool> {
+    value
+        .as_bool()
+        .ok_or_else(|| Error::new(format!("parameter '{}' must be a boolean", name)))
+}
+
+/// Parse a comma separated string or a list of values.
+fn parse_list<T>(
+    name: &str,
+    value: &Value,
+    parse: fn(&str, &Value) -> Result<T>,
+) -> Result<Vec<T>> {
+    match value {
+        Value::Array(values) => values.iter().map(|v| parse(name, v)).collect(),
+        Value::String(s) => s
+            .split(',')
+            .filter(|v| !v.trim().is_empty())
+            .map(|v| parse(name, &serde_json::from_str(v.trim()).unwrap_or(Value::Null)))
+            .collect(),
+        _ => Err(Error::new(format!(
+            "parameter '{}' must be a string or a list",
+            name
+        ))),
+    }
+}
+
+/// Parse a comma separated string or a list of metric names.
+fn parse_metrics(value: &Value) -> Result<Vec<MetricType>> {
+    let names = match value {
+        Value::String(s) => s.split(',').map(String::from).collect::<Vec<_>>(),
+        Value::Array(values) => values
+            .iter()
+            .map(|v| parse_str("metric", v).map(String::from))
+            .collect::<Result<Vec<_>>>()?,
+        _ => {
+            return Err(Error::new(
+                "parameter 'metric' must be a string or a list of strings",
+            ))
+        }
+    };
+    let disabled = ["", "none", "null", "custom", "na"]
+        .iter()
+        .cloned()
+        .collect::<HashSet<_>>();
+    names
+        .iter()
+        .filter(|name| !disabled.contains(name.trim().to_lowercase().as_str()))
+        .map(|name| name.parse())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn enum_names() {
+        assert_eq!(
+            "softmax".parse::<ObjectiveType>(),
+            Ok(ObjectiveType::Multiclass)
+        );
+        assert_eq!("Binary".parse::<ObjectiveType>(), Ok(ObjectiveType::Binary));
+        assert!("binray".parse::<ObjectiveType>().is_err());
+        assert_eq!(ObjectiveType::RankXendcg.as_str(), "rank_xendcg");
+        assert_eq!(
+            "random_forest".parse::<BoostingType>(),
+            Ok(BoostingType::Rf)
+        );
+        assert_eq!(MetricType::Rmse.to_string(), "rmse");
+    }
+
+    #[test]
+    fn from_json() {
+        let params = TrainParams::from_json(&json! {
+            {
+                "n_estimators": 10,
+                "objective": "binary",
+                "metric": ["auc", "binary_logloss"],
+                "eta": 0.1,
+                "bagging_seed": 3
+            }
+        })
+        .unwrap();
+        assert_eq!(params.num_iterations, Some(10));
+        assert_eq!(params.objective, Some(ObjectiveType::Binary));
+        assert_eq!(
+            params.metric,
+            Some(vec![MetricType::Auc, MetricType::BinaryLogloss])
+        );
+        assert_eq!(params.learning_rate, Some(0.1));
+        assert_eq!(params.extra.get("bagging_seed"), Some(&json!(3)));
+    }
+
+    #[test]
+    fn from_json_errors() {
+        assert!(TrainParams::from_json(&json!([1, 2])).is_err());
+        assert_eq!(
+            TrainParams::from_json(&json! {{"num_iteration": 10, "num_iterations": 5}}),
+            Err(Error::new(
+                "parameter 'num_iterations' is set twice, as 'num_iteration' and 'num_iterations'"
+            ))
+        );
+        assert_eq!(
+            TrainParams::from_json(&json! {{"num_leafs": 10}}),
+            Err(Error::new("unknown parameter 'num_leafs'"))
+        );
+        assert_eq!(
+            TrainParams::from_json(&json! {{"num_leaves": "31"}}),
+            Err(Error::new("parameter 'num_leaves' must be an integer"))
+        );
+        assert_eq!(
+            TrainParams::from_json(&json! {{"learning_rate": true}}),
+            Err(Error::new("parameter 'learning_rate' must be a number"))
+        );
+    }
+
+    #[test]
+    fn ranking_params() {
+        let params = TrainParams::from_json(&json! {
+            {
+                "objective": "lambdarank",
+                "ndcg_eval_at": "1, 3,5",
+                "label_gain": [0, 1, 3.5]
+            }
+        })
+        .unwrap();
+        assert_eq!(params.objective, Some(ObjectiveType::LambdaRank));
+        assert_eq!(params.eval_at, Some(vec![1, 3, 5]));
+        assert_eq!(params.label_gain, Some(vec![0.0, 1.0, 3.5]));
+        assert_eq!(
+            params.to_param_string(),
+            Ok(String::from(
+                "eval_at=1,3,5 label_gain=0.0,1.0,3.5 objective=lambdarank"
+            ))
+        );
+        assert_eq!(
+            TrainParams::from_json(&json! {{"eval_at": "1,top"}}),
+            Err(Error::new("parameter 'eval_at' must be an integer"))
+        );
+    }
+
+    #[test]
+    fn builder() {
+        let params = TrainParamsBuilder::default()
+            .objective(ObjectiveType::Regression)
+            .metric(vec![])
+            .num_leaves(63)
+            .param("min_data", 5)
+            .param("bagging_seed", 3)
+            .build()
+            .unwrap();
+        assert_eq!(params.min_data_in_leaf, Some(5));
+        assert_eq!(
+            params.to_json(),
+            json! {
+                {
+                    "objective": "regression",
+                    "metric": "None",
+                    "num_leaves": 63,
+                    "min_data_in_leaf": 5,
+                    "bagging_seed": 3
+                }
+            }
+        );
+
+        let result = TrainParamsBuilder::default()
+            .num_leaves(63)
+            .param("num_leaf", 31)
+            .build();
+        assert!(result.is_err());
+
+        let result = TrainParamsBuilder::default().param("foo", 1).build();
+        assert_eq!(result, Err(Error::new("unknown parameter 'foo'")));
+    }
+
+    #[test]
+    fn param_string() {
+        let params = json! {
+            {
+                "objective": "binary",
+                "metric": ["auc", "binary_logloss"],
+                "monotone_constraints": [1, 0, -1],
+                "learning_rate": 0.05,
+                "is_unbalance": true,
+                "verbose": -1
+            }
+        };
+        assert_eq!(
+            super::param_string(params.as_object().unwrap()),
+            Ok(String::from(
+                "is_unbalance=true learning_rate=0.05 metric=auc,binary_logloss \
+                 monotone_constraints=1,0,-1 objective=binary verbose=-1"
+            ))
+        );
+
+        for invalid in &[
+            json! {{"objective": null}},
+            json! {{"objective": {"name": "binary"}}},
+            json! {{"metric": [["auc"]]}},
+            json! {{"output_model": "my model.txt"}},
+        ] {
+            assert!(super::param_string(invalid.as_object().unwrap()).is_err());
+        }
+    }
+
+    #[test]
+    fn set() {
+        let mut params = TrainParams::default();
+        params.set("num_threads", 2).unwrap();
+        params.set("nthread", 4).unwrap();
+        assert_eq!(params.num_threads, Some(4));
+        assert!(params.set("objective", 1).is_err());
+    }
+
+    #[test]
+    fn dataset_params() {
+        let params = DatasetParamsBuilder::default()
+            .feature_names(vec![
+                String::from("a"),
+                String::from("b"),
+                String::from("c"),
+            ])
+            .categorical_feature(vec![2, 0])
+            .categorical_feature_names(vec![String::from("c"), String::from("b")])
+            .build()
+            .unwrap();
+        assert_eq!(
+            params.to_param_string().unwrap(),
+            "categorical_feature=0,1,2"
+        );
+        assert_eq!(DatasetParams::default().to_param_string().unwrap(), "");
+
+        let params = DatasetParamsBuilder::default()
+            .max_bin(63)
+            .min_data_in_bin(5)
+            .bin_construct_sample_cnt(1000)
+            .build()
+            .unwrap();
+        assert_eq!(
+            params.to_param_string().unwrap(),
+            "bin_construct_sample_cnt=1000 max_bin=63 min_data_in_bin=5"
+        );
+
+        assert!(DatasetParamsBuilder::default()
+            .categorical_feature(vec![-1])
+            .build()
+            .is_err());
+        assert!(DatasetParamsBuilder::default()
+            .feature_names(vec![String::from("a")])
+            .categorical_feature_names(vec![String::from("b")])
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,468 @@
+//! Prediction types and shaped prediction results.
+
+use libc::c_void;
+use lightgbm_sys;
+use serde_json::{Map, Value};
+use std::marker::PhantomData;
+use std::slice;
+
+use crate::params::{canonical_name, param_aliases, param_string};
+use crate::{DataType, Error, IndexType, Result};
+
+/// Kind of values predicted by a [`Booster`](crate::Booster).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PredictType {
+    /// Predictions after the objective's transformation, e.g. probabilities.
+    #[default]
+    Normal,
+    /// Raw scores before the objective's transformation.
+    RawScore,
+    /// Index of the leaf reached in each tree.
+    LeafIndex,
+    /// SHAP feature contributions, with the expected value of the model last.
+    Contrib,
+}
+
+impl PredictType {
+    pub(crate) fn c_api(self) -> i32 {
+        let predict_type = match self {
+            PredictType::Normal => lightgbm_sys::C_API_PREDICT_NORMAL,
+            PredictType::RawScore => lightgbm_sys::C_API_PREDICT_RAW_SCORE,
+            PredictType::LeafIndex => lightgbm_sys::C_API_PREDICT_LEAF_INDEX,
+            PredictType::Contrib => lightgbm_sys::C_API_PREDICT_CONTRIB,
+        };
+        predict_type as i32
+    }
+}
+
+/// Options for [`Booster::predict_with_options`](crate::Booster::predict_with_options).
+///
+/// Example
+/// ```
+/// use lightgbm::{Booster, PredictOptions, PredictType};
+///
+/// let booster = Booster::from_file(&"test/test_from_file.input").unwrap();
+/// let data = vec![0.5_f32; 28];
+/// // raw scores of the first 5 iterations, stopping early on confident rows
+/// let options = PredictOptions::new()
+///     .predict_type(PredictType::RawScore)
+///     .num_iteration(5)
+///     .param("pred_early_stop", true)
+///     .param("pred_early_stop_margin", 2.0);
+/// let raw = booster.predict_with_options(&data, 28, &options).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PredictOptions {
+    pub(crate) predict_type: PredictType,
+    pub(crate) start_iteration: i32,
+    pub(crate) num_iteration: Option<i32>,
+    pub(crate) params: Map<String, Value>,
+}
+
+impl PredictOptions {
+    /// Create default options: normal predictions of all iterations, up to
+    /// the best iteration if the model was trained with early stopping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the kind of values to predict.
+    pub fn predict_type(mut self, predict_type: PredictType) -> Self {
+        self.predict_type = predict_type;
+        self
+    }
+
+    /// Start predicting from the iteration at index `start_iteration`.
+    pub fn start_iteration(mut self, start_iteration: i32) -> Self {
+        self.start_iteration = start_iteration;
+        self
+    }
+
+    /// Predict with `num_iteration` iterations from the start iteration, `<= 0` means all.
+    pub fn num_iteration(mut self, num_iteration: i32) -> Self {
+        self.num_iteration = Some(num_iteration);
+        self
+    }
+
+    /// Set a LightGBM prediction parameter by name or alias, such as
+    /// `pred_early_stop` or `num_threads`.
+    ///
+    /// Unknown names are reported when predicting.
+    pub fn param<V: Into<Value>>(mut self, key: &str, value: V) -> Self {
+        self.params.insert(String::from(key), value.into());
+        self
+    }
+
+    /// Start and number of iterations to predict with, for a model whose best
+    /// iteration is `best_iteration`.
+    pub(crate) fn iteration_range(&self, best_iteration: Option<i32>) -> Result<(i32, i32)> {
+        if self.start_iteration < 0 {
+            return Err(Error::new(format!(
+                "start_iteration ({}) must not be negative",
+                self.start_iteration
+            )));
+        }
+        let num_iteration = match (self.num_iteration, best_iteration) {
+            (Some(num_iteration), _) => num_iteration,
+            (None, Some(best)) if best > self.start_iteration => best - self.start_iteration,
+            (None, Some(best)) => {
+                return Err(Error::new(format!(
+                    "start_iteration ({}) is past the best iteration ({})",
+                    self.start_iteration, best
+                )))
+            }
+            (None, None) => -1, // -1 means no limit
+        };
+        Ok((self.start_iteration, num_iteration))
+    }
+
+    /// Format the prediction parameters as a LightGBM parameter string.
+    pub(crate) fn param_string(&self) -> Result<String> {
+        if self.params.is_empty() {
+            return Ok(String::new());
+        }
+        let aliases = param_aliases()?;
+        for key in self.params.keys() {
+            canonical_name(&aliases, key)?;
+        }
+        param_string(&self.params)
+    }
+}
+
+/// Predictions of several rows, shaped `rows × classes × values`.
+///
+/// There is one value per row and class for [`PredictType::Normal`] and
+/// [`PredictType::RawScore`], one leaf index per iteration for
+/// [`PredictType::LeafIndex`], and one contribution per feature followed by
+/// the expected value for [`PredictType::Contrib`].
+///
+/// Example
+/// ```
+/// use lightgbm::{Booster, PredictType};
+///
+/// let booster = Booster::from_file(&"test/test_from_file.input").unwrap();
+/// let num_feature = booster.num_feature().unwrap() as usize;
+/// let data = vec![0.5_f32; 2 * num_feature];
+/// let contrib = booster
+///     .predict_with_type(&data, num_feature as i32, PredictType::Contrib)
+///     .unwrap();
+/// assert_eq!(contrib.shape(), (2, 1, num_feature + 1));
+/// let shap = contrib.get(0, 0);
+/// let expected_value = shap[num_feature];
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Predictions {
+    values: Vec<f64>,
+    num_rows: usize,
+    num_classes: usize,
+    num_values: usize,
+}
+
+impl Predictions {
+    /// Shape predictions laid out as LightGBM returns them for `predict_type`.
+    pub(crate) fn new(
+        predict_type: PredictType,
+        values: Vec<f64>,
+        num_rows: usize,
+        num_classes: usize,
+    ) -> Result<Self> {
+        let row_len = values.len().checked_div(num_rows).unwrap_or(0);
+        let num_values = row_len.checked_div(num_classes).unwrap_or(0);
+        if num_rows * num_classes * num_values != values.len() {
+            return Err(Error::new(format!(
+                "cannot shape {} predictions as {} rows and {} classes",
+                values.len(),
+                num_rows,
+                num_classes
+            )));
+        }
+        let values = if predict_type == PredictType::LeafIndex && num_classes > 1 && row_len > 0 {
+            // leaf indices are ordered by iteration, then by class
+            values
+                .chunks(row_len)
+                .flat_map(|row| {
+                    (0..num_classes)
+                        .flat_map(move |class| row.iter().skip(class).step_by(num_classes).cloned())
+                })
+                .collect()
+        } else {
+            values
+        };
+        Ok(Self {
+            values,
+            num_rows,
+            num_classes,
+            num_values,
+        })
+    }
+
+    /// Get the number of rows, classes and values per class.
+    pub fn shape(&self) -> (usize, usize, usize) {
+        (self.num_rows, self.num_classes, self.num_values)
+    }
+
+    /// Get the values of all classes of `row`.
+    pub fn row(&self, row: usize) -> &[f64] {
+        let row_len = self.num_classes * self.num_values;
+        &self.values[row * row_len..(row + 1) * row_len]
+    }
+
+    /// Get the values of `class` for `row`.
+    pub fn get(&self, row: usize, class: usize) -> &[f64] {
+        let start = (row * self.num_classes + class) * self.num_values;
+        &self.values[start..start + self.num_values]
+    }
+
+    /// Iterate over the values of all classes of each row.
+    pub fn rows(&self) -> std::slice::Chunks<'_, f64> {
+        self.values
+            .chunks((self.num_classes * self.num_values).max(1))
+    }
+
+    /// Get the values of all classes of each row.
+    pub fn to_rows(&self) -> Vec<Vec<f64>> {
+        self.rows().map(|row| row.to_vec()).collect()
+    }
+
+    /// Get the class with the largest value of each row, the first one on ties.
+    ///
+    /// Meant for [`PredictType::Normal`] and [`PredictType::RawScore`]
+    /// predictions, the first value of each class is compared.
+    pub fn argmax(&self) -> Vec<usize> {
+        (0..self.num_rows)
+            .map(|row| {
+                (0..self.num_classes)
+                    .map(|class| self.get(row, class)[0])
+                    .enumerate()
+                    .fold((0, f64::NEG_INFINITY), |best, (class, value)| {
+                        if value > best.1 {
+                            (class, value)
+                        } else {
+                            best
+                        }
+                    })
+                    .0
+            })
+            .collect()
+    }
+
+    /// Get all values in row-major order.
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Take all values in row-major order.
+    pub fn into_values(self) -> Vec<f64> {
+        self.values
+    }
+}
+
+/// SHAP feature contributions of a sparse input, as sparse matrices owned by LightGBM.
+///
+/// There is one matrix per class, in the format of the input: CSR matrices
+/// of `rows × (features + 1)` for [`Booster::predict_contrib_csr`](crate::Booster::predict_contrib_csr)
+/// and CSC matrices for [`Booster::predict_contrib_csc`](crate::Booster::predict_contrib_csc).
+/// The last column holds the expected value of the model. Index and value
+/// types are those of the input.
+///
+/// Example
+/// ```
+/// use lightgbm::{Booster, PredictOptions};
+///
+/// let booster = Booster::from_file(&"test/test_from_file.input").unwrap();
+/// let indptr: Vec<i32> = vec![0, 2, 3];
+/// let indices = vec![0, 5, 27];
+/// let data: Vec<f64> = vec![1.0, 0.3, 0.8];
+/// let contrib = booster
+///     .predict_contrib_csr(&indptr, &indices, &data, 28, &PredictOptions::new())
+///     .unwrap();
+/// assert_eq!(contrib.shape(), (2, 29));
+/// // contributions of the first row, by feature index
+/// let row = contrib.indptr(0)[0] as usize..contrib.indptr(0)[1] as usize;
+/// for (feature, value) in contrib.indices(0)[row.clone()].iter().zip(&contrib.values(0)[row]) {
+///     println!("{}: {}", feature, value);
+/// }
+/// ```
+pub struct SparseContributions<I: IndexType, T: DataType> {
+    indptr: *mut c_void,
+    indices: *mut i32,
+    data: *mut c_void,
+    indptr_len: usize,
+    /// Length of the indptr of each class.
+    class_indptr_len: usize,
+    /// Start of the indices and values of each class.
+    offsets: Vec<usize>,
+    shape: (usize, usize),
+    phantom: PhantomData<(I, T)>,
+}
+
+impl<I: IndexType, T: DataType> SparseContributions<I, T> {
+    /// Take ownership of the matrices returned by `LGBM_BoosterPredictSparseOutput`.
+    ///
+    /// `out_len` holds the number of values and the length of the indptr, each
+    /// class has an indptr of `class_indptr_len` offsets starting at zero.
+    pub(crate) unsafe fn from_raw(
+        indptr: *mut c_void,
+        indices: *mut i32,
+        data: *mut c_void,
+        out_len: [i64; 2],
+        class_indptr_len: usize,
+        shape: (usize, usize),
+    ) -> Result<Self> {
+        let mut contrib = Self {
+            indptr,
+            indices,
+            data,
+            indptr_len: out_len[1] as usize,
+            class_indptr_len,
+            offsets: Vec::new(),
+            shape,
+            phantom: PhantomData,
+        };
+        let num_classes = contrib
+            .indptr_len
+            .checked_div(class_indptr_len)
+            .unwrap_or(0);
+        if num_classes == 0 || num_classes * class_indptr_len != contrib.indptr_len {
+            return Err(Error::new(format!(
+                "cannot split a sparse output indptr of length {} by {}",
+                contrib.indptr_len, class_indptr_len
+            )));
+        }
+        let mut offset = 0;
+        for class in 0..num_classes {
+            contrib.offsets.push(offset);
+            let indptr = contrib.indptr(class);
+            offset += indptr[indptr.len() - 1].into() as usize;
+        }
+        if offset != out_len[0] as usize {
+            return Err(Error::new(format!(
+                "sparse output has {} values, expected {}",
+                out_len[0], offset
+            )));
+        }
+        Ok(contrib)
+    }
+
+    /// Get the number of classes, each with its own matrix.
+    pub fn num_classes(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Get the number of rows and columns of each matrix, including the expected value column.
+    pub fn shape(&self) -> (usize, usize) {
+        self.shape
+    }
+
+    /// Get the row offsets of a CSR matrix or column offsets of a CSC matrix of `class`.
+    pub fn indptr(&self, class: usize) -> &[I] {
+        let indptr = unsafe { slice::from_raw_parts(self.indptr as *const I, self.indptr_len) };
+        &indptr[class * self.class_indptr_len..(class + 1) * self.class_indptr_len]
+    }
+
+    /// Get the column indices of a CSR matrix or row indices of a CSC matrix of `class`.
+    pub fn indices(&self, class: usize) -> &[i32] {
+        let len = self.class_len(class);
+        unsafe { slice::from_raw_parts(self.indices.add(self.offsets[class]), len) }
+    }
+
+    /// Get the contributions of `class`.
+    pub fn values(&self, class: usize) -> &[T] {
+        let len = self.class_len(class);
+        unsafe { slice::from_raw_parts((self.data as *const T).add(self.offsets[class]), len) }
+    }
+
+    fn class_len(&self, class: usize) -> usize {
+        let indptr = self.indptr(class);
+        indptr[indptr.len() - 1].into() as usize
+    }
+}
+
+impl<I: IndexType, T: DataType> Drop for SparseContributions<I, T> {
+    fn drop(&mut self) {
+        lgbm_call!(lightgbm_sys::LGBM_BoosterFreePredictSparse(
+            self.indptr,
+            self.indices,
+            self.data,
+            I::DTYPE,
+            T::DTYPE
+        ))
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shape() {
+        let values = (0..12).map(f64::from).collect::<Vec<_>>();
+        let predictions = Predictions::new(PredictType::Contrib, values, 2, 2).unwrap();
+        assert_eq!(predictions.shape(), (2, 2, 3));
+        assert_eq!(predictions.row(1), &[6.0, 7.0, 8.0, 9.0, 10.0, 11.0]);
+        assert_eq!(predictions.get(1, 0), &[6.0, 7.0, 8.0]);
+
+        assert!(Predictions::new(PredictType::Normal, vec![0.0; 5], 2, 1).is_err());
+        let empty = Predictions::new(PredictType::Normal, Vec::new(), 0, 3).unwrap();
+        assert_eq!(empty.shape(), (0, 3, 0));
+        assert_eq!(empty.rows().count(), 0);
+        assert!(empty.argmax().is_empty());
+    }
+
+    #[test]
+    fn multiclass_rows() {
+        let values = vec![0.2, 0.5, 0.3, 0.6, 0.1, 0.3];
+        let predictions = Predictions::new(PredictType::Normal, values, 2, 3).unwrap();
+        assert_eq!(
+            predictions.to_rows(),
+            vec![vec![0.2, 0.5, 0.3], vec![0.6, 0.1, 0.3]]
+        );
+        assert_eq!(predictions.rows().nth(1).unwrap(), &[0.6, 0.1, 0.3]);
+        assert_eq!(predictions.argmax(), vec![1, 0]);
+    }
+
+    #[test]
+    fn iteration_range() {
+        let options = PredictOptions::new();
+        assert_eq!(options.iteration_range(None).unwrap(), (0, -1));
+        assert_eq!(options.iteration_range(Some(7)).unwrap(), (0, 7));
+
+        let options = PredictOptions::new().start_iteration(3);
+        assert_eq!(options.iteration_range(Some(7)).unwrap(), (3, 4));
+        assert!(options.iteration_range(Some(3)).is_err());
+        assert_eq!(
+            options.num_iteration(2).iteration_range(Some(3)).unwrap(),
+            (3, 2)
+        );
+        assert!(PredictOptions::new()
+            .start_iteration(-1)
+            .iteration_range(None)
+            .is_err());
+    }
+
+    #[test]
+    fn predict_param_string() {
+        let options = PredictOptions::new()
+            .param("pred_early_stop", true)
+            .param("num_threads", 2);
+        assert_eq!(
+            options.param_string().unwrap(),
+            "num_threads=2 pred_early_stop=true"
+        );
+        assert!(PredictOptions::new()
+            .param("pred_early_stops", true)
+            .param_string()
+            .is_err());
+    }
+
+    #[test]
+    fn leaf_index_by_class() {
+        // two iterations of three classes, for one row
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let predictions = Predictions::new(PredictType::LeafIndex, values, 1, 3).unwrap();
+        assert_eq!(predictions.shape(), (1, 3, 2));
+        assert_eq!(predictions.get(0, 0), &[1.0, 4.0]);
+        assert_eq!(predictions.get(0, 2), &[3.0, 6.0]);
+    }
+}
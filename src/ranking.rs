@@ -0,0 +1,220 @@
+//! Learning-to-rank with query groups.
+//!
+//! Ranking objectives (`lambdarank`, `rank_xendcg`) and metrics (`ndcg`, `map`)
+//! need the rows of each query to be contiguous and the number of rows of each
+//! query to be set on the `Dataset`. The helpers here derive those group sizes
+//! from a per-row query id, and rank predictions within each query.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use crate::{Booster, Dataset, Error, Result};
+
+/// Convert per-row query ids into the number of rows of each query.
+///
+/// The rows of each query must be contiguous.
+///
+/// Example
+/// ```
+/// use lightgbm::query_group_sizes;
+///
+/// assert_eq!(query_group_sizes(&["a", "a", "b", "c", "c", "c"]).unwrap(), vec![2, 1, 3]);
+/// assert!(query_group_sizes(&["a", "b", "a"]).is_err());
+/// ```
+pub fn query_group_sizes<Q: Eq + Hash>(query: &[Q]) -> Result<Vec<i32>> {
+    let mut sizes = Vec::new();
+    let mut seen = HashSet::new();
+    for (i, id) in query.iter().enumerate() {
+        if i > 0 && query[i - 1] == *id {
+            *sizes.last_mut().unwrap() += 1;
+            continue;
+        }
+        if !seen.insert(id) {
+            return Err(Error::new(format!(
+                "rows of a query must be contiguous, row {} continues an earlier query",
+                i
+            )));
+        }
+        sizes.push(1);
+    }
+    Ok(sizes)
+}
+
+/// A row of a query, ranked by its predicted score.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RankedRow {
+    /// Index of the row in the predicted data.
+    pub row: usize,
+    /// Predicted relevance score.
+    pub score: f64,
+}
+
+impl Dataset {
+    /// Create a new `Dataset` for ranking from a dense array in row-major order
+    /// and the query id of every row.
+    ///
+    /// See [`Booster::predict_ranked`] for an example.
+    pub fn from_vec_with_query<Q: Eq + Hash>(
+        data: &[f32],
+        labels: &[f32],
+        num_features: i32,
+        query: &[Q],
+    ) -> Result<Self> {
+        let mut dataset = Self::from_vec(data, labels, num_features)?;
+        dataset.set_query(query)?;
+        Ok(dataset)
+    }
+
+    /// Set the query groups from the query id of every row.
+    pub fn set_query<Q: Eq + Hash>(&mut self, query: &[Q]) -> Result<()> {
+        self.set_group(&query_group_sizes(query)?)
+    }
+}
+
+impl Booster {
+    /// Predict relevance scores and rank the rows of each query, best first.
+    ///
+    /// Returns one ranking per query in order of appearance, truncated to
+    /// the `top_k` best rows if given.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::{Booster, Dataset, MetricType, ObjectiveType, TrainParamsBuilder};
+    ///
+    /// let data = vec![1.0, 0.1, 0.2, 0.1,
+    ///                0.7, 0.4, 0.5, 0.1,
+    ///                0.9, 0.8, 0.5, 0.1,
+    ///                0.2, 0.2, 0.8, 0.7,
+    ///                0.1, 0.7, 1.0, 0.9];
+    /// let relevance = vec![2.0, 1.0, 0.0, 0.0, 1.0];
+    /// let query = vec![10, 10, 10, 11, 11];
+    /// let dataset = Dataset::from_vec_with_query(&data, &relevance, 4, &query).unwrap();
+    ///
+    /// let params = TrainParamsBuilder::default()
+    ///     .objective(ObjectiveType::LambdaRank)
+    ///     .metric(vec![MetricType::Ndcg])
+    ///     .eval_at(vec![1, 3])
+    ///     .num_iterations(5)
+    ///     .param("min_data_in_leaf", 1)
+    ///     .build()
+    ///     .unwrap();
+    /// let bst = Booster::train(dataset, &params).unwrap();
+    ///
+    /// // best two rows of each query
+    /// let ranked = bst.predict_ranked(&data, 4, &query, Some(2)).unwrap();
+    /// assert_eq!(ranked.len(), 2);
+    /// assert_eq!(ranked[0].len(), 2);
+    /// ```
+    pub fn predict_ranked<Q: Eq + Hash>(
+        &self,
+        data: &[f32],
+        num_features: i32,
+        query: &[Q],
+        top_k: Option<usize>,
+    ) -> Result<Vec<Vec<RankedRow>>> {
+        let sizes = query_group_sizes(query)?;
+        let scores = self.predict(data, num_features)?;
+        if scores.len() != query.len() {
+            return Err(Error::new(format!(
+                "got {} predictions for {} query ids",
+                scores.len(),
+                query.len()
+            )));
+        }
+
+        let mut start = 0;
+        let mut ranked = Vec::with_capacity(sizes.len());
+        for size in sizes {
+            let end = start + size as usize;
+            let mut rows = (start..end)
+                .map(|row| RankedRow {
+                    row,
+                    score: scores[row],
+                })
+                .collect::<Vec<_>>();
+            rows.sort_by(|a, b| b.score.total_cmp(&a.score));
+            if let Some(k) = top_k {
+                rows.truncate(k);
+            }
+            ranked.push(rows);
+            start = end;
+        }
+        Ok(ranked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MetricType, ObjectiveType, TrainOptions, TrainParamsBuilder};
+
+    #[test]
+    fn group_sizes() {
+        assert_eq!(query_group_sizes::<i32>(&[]).unwrap(), Vec::<i32>::new());
+        assert_eq!(query_group_sizes(&[7, 7, 7]).unwrap(), vec![3]);
+        assert_eq!(query_group_sizes(&[1, 2, 2, 3]).unwrap(), vec![1, 2, 1]);
+        assert_eq!(
+            query_group_sizes(&[1, 2, 1]),
+            Err(Error::new(
+                "rows of a query must be contiguous, row 2 continues an earlier query"
+            ))
+        );
+    }
+
+    #[test]
+    fn train_lambdarank() {
+        // the .query files next to the data are loaded as groups
+        let train =
+            Dataset::from_file(&"lightgbm-sys/lightgbm/examples/lambdarank/rank.train").unwrap();
+        let valid = Dataset::from_file_with_reference(
+            &"lightgbm-sys/lightgbm/examples/lambdarank/rank.test",
+            &train,
+        )
+        .unwrap();
+        assert!(!train.group().unwrap().is_empty());
+
+        let params = TrainParamsBuilder::default()
+            .objective(ObjectiveType::LambdaRank)
+            .metric(vec![MetricType::Ndcg])
+            .eval_at(vec![1, 5])
+            .num_iterations(5)
+            .build()
+            .unwrap();
+        let options = TrainOptions::new().valid_set("valid", &valid);
+        let bst = Booster::train_with_options(train, &params, options).unwrap();
+        assert_eq!(
+            bst.eval_history().metrics("valid"),
+            vec!["ndcg@1", "ndcg@5"]
+        );
+    }
+
+    #[test]
+    fn predict_ranked() {
+        let data = vec![
+            1.0, 0.1, 0.2, 0.1, 0.7, 0.4, 0.5, 0.1, 0.9, 0.8, 0.5, 0.1, 0.2, 0.2, 0.8, 0.7, 0.1,
+            0.7, 1.0, 0.9,
+        ];
+        let relevance = vec![2.0, 1.0, 0.0, 0.0, 1.0];
+        let query = vec!["q1", "q1", "q1", "q2", "q2"];
+        let dataset = Dataset::from_vec_with_query(&data, &relevance, 4, &query).unwrap();
+        assert_eq!(dataset.group().unwrap(), vec![3, 2]);
+
+        let params = TrainParamsBuilder::default()
+            .objective(ObjectiveType::RankXendcg)
+            .num_iterations(5)
+            .param("min_data_in_leaf", 1)
+            .build()
+            .unwrap();
+        let bst = Booster::train(dataset, &params).unwrap();
+
+        let ranked = bst.predict_ranked(&data, 4, &query, None).unwrap();
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].len(), 3);
+        assert!(ranked[0].windows(2).all(|w| w[0].score >= w[1].score));
+        assert!(ranked[1].iter().all(|r| r.row == 3 || r.row == 4));
+
+        let top = bst.predict_ranked(&data, 4, &query, Some(1)).unwrap();
+        assert_eq!(top[0], vec![ranked[0][0]]);
+        assert!(bst.predict_ranked(&data, 4, &query[..4], None).is_err());
+    }
+}
@@ -0,0 +1,65 @@
+//! Small seeded random number generator, so results are reproducible without extra dependencies.
+
+/// SplitMix64 generator.
+#[derive(Clone, Debug)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform integer in `0..n`, `n` must be positive.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Uniform float in `[0, 1)`.
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    /// Fisher-Yates shuffle.
+    pub(crate) fn shuffle<T>(&mut self, values: &mut [T]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shuffle() {
+        let mut values = (0..100).collect::<Vec<_>>();
+        Rng::new(42).shuffle(&mut values);
+        assert_ne!(values, (0..100).collect::<Vec<_>>());
+
+        let mut again = (0..100).collect::<Vec<_>>();
+        Rng::new(42).shuffle(&mut again);
+        assert_eq!(values, again);
+
+        values.sort();
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn next_f64() {
+        let mut rng = Rng::new(0);
+        assert!((0..1000)
+            .map(|_| rng.next_f64())
+            .all(|v| (0.0..1.0).contains(&v)));
+    }
+}
@@ -0,0 +1,563 @@
+//! Streaming construction of a `Dataset` from batches of rows.
+
+use libc::{c_char, c_double, c_int, c_void};
+use lightgbm_sys;
+use std;
+use std::ffi::CString;
+
+use crate::dataset::check_compressed;
+use crate::{DataType, Dataset, DatasetParams, Error, IndexType, Result};
+
+/// Labels and optional metadata of a batch of rows pushed to a [`DatasetBuilder`].
+///
+/// The optional fields must be given for either all batches or none.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RowMetadata<'a> {
+    /// Label of every row.
+    pub label: &'a [f32],
+    /// Weight of every row.
+    pub weight: Option<&'a [f32]>,
+    /// Initial score of every row and class, class-major.
+    pub init_score: Option<&'a [f64]>,
+    /// Query id of every row, for ranking.
+    pub query: Option<&'a [i32]>,
+}
+
+impl<'a> RowMetadata<'a> {
+    /// Metadata with labels only.
+    pub fn new(label: &'a [f32]) -> Self {
+        Self {
+            label,
+            ..Self::default()
+        }
+    }
+}
+
+/// Fields present in every batch, fixed by the first batch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Layout {
+    has_weight: bool,
+    has_query: bool,
+    num_class: usize,
+}
+
+/// Build a `Dataset` by pushing batches of rows, without holding all rows in memory.
+///
+/// The bin boundaries come from a reference dataset, possibly serialized in
+/// another process, or from a sample of the rows, see [`DatasetBuilder::from_sample`].
+/// The total number of rows must be known upfront.
+///
+/// Example
+/// ```
+/// use lightgbm::{Dataset, DatasetBuilder, DatasetParams, RowMetadata};
+///
+/// let sample = Dataset::from_vec(&[1.0, 0.1, 0.7, 0.4, 0.9, 0.8], &[0.0, 0.0, 1.0], 2).unwrap();
+///
+/// let params = DatasetParams::default();
+/// let mut builder = DatasetBuilder::from_reference(&sample, 4, &params).unwrap();
+/// builder.push_rows(&[1.0_f32, 0.1, 0.7, 0.4], RowMetadata::new(&[0.0, 0.0])).unwrap();
+/// let batch = RowMetadata::new(&[1.0, 1.0]);
+/// builder.push_rows(&[0.9_f32, 0.8, 0.2, 0.7], batch).unwrap();
+/// let dataset = builder.finish().unwrap();
+/// ```
+pub struct DatasetBuilder {
+    dataset: Dataset,
+    num_total_row: usize,
+    num_col: usize,
+    num_pushed: usize,
+    layout: Option<Layout>,
+}
+
+impl DatasetBuilder {
+    /// Start a dataset of `num_total_row` rows, binned like `reference`.
+    ///
+    /// The bins and categorical features come from `reference`, so only the
+    /// feature names of `params` are applied.
+    pub fn from_reference(
+        reference: &Dataset,
+        num_total_row: usize,
+        params: &DatasetParams,
+    ) -> Result<Self> {
+        let mut num_col = 0;
+        lgbm_call!(lightgbm_sys::LGBM_DatasetGetNumFeature(
+            reference.handle,
+            &mut num_col
+        ))?;
+
+        let mut handle = std::ptr::null_mut();
+        lgbm_call!(lightgbm_sys::LGBM_DatasetCreateByReference(
+            reference.handle,
+            num_total_row as i64,
+            &mut handle
+        ))?;
+
+        let dataset = Dataset::new(handle).with_feature_names(params)?;
+        Ok(Self::new(dataset, num_total_row, num_col as usize))
+    }
+
+    /// Start a dataset of `num_total_row` rows, binned like the dataset
+    /// serialized with [`Dataset::serialize_reference`].
+    ///
+    /// As with [`DatasetBuilder::from_reference`], only the feature names of
+    /// `params` are applied.
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::{Dataset, DatasetBuilder, DatasetParams, RowMetadata};
+    ///
+    /// let sample = Dataset::from_vec(&[1.0, 0.1, 0.7, 0.4, 0.9, 0.8], &[0.0, 0.0, 1.0], 2).unwrap();
+    /// let reference = sample.serialize_reference().unwrap();
+    ///
+    /// // e.g. in a worker process
+    /// let params = DatasetParams::default();
+    /// let mut builder = DatasetBuilder::from_serialized_reference(&reference, 2, &params).unwrap();
+    /// builder.push_rows(&[0.9_f32, 0.8, 0.2, 0.7], RowMetadata::new(&[1.0, 1.0])).unwrap();
+    /// let dataset = builder.finish().unwrap();
+    /// ```
+    pub fn from_serialized_reference(
+        reference: &[u8],
+        num_total_row: usize,
+        params: &DatasetParams,
+    ) -> Result<Self> {
+        let params_str = CString::new(params.to_param_string()?).unwrap();
+        let mut handle = std::ptr::null_mut();
+        lgbm_call!(lightgbm_sys::LGBM_DatasetCreateFromSerializedReference(
+            reference.as_ptr() as *const c_void,
+            reference.len() as i32,
+            num_total_row as i64,
+            1,
+            params_str.as_ptr() as *const c_char,
+            &mut handle
+        ))?;
+        let dataset = Dataset::new(handle).with_feature_names(params)?;
+
+        let mut num_col = 0;
+        lgbm_call!(lightgbm_sys::LGBM_DatasetGetNumFeature(
+            dataset.handle,
+            &mut num_col
+        ))?;
+        Ok(Self::new(dataset, num_total_row, num_col as usize))
+    }
+
+    /// Start a dataset of `num_total_row` rows, binned from a sample of the rows.
+    ///
+    /// `sample` holds the sampled rows in row-major order, typically the rows
+    /// at the [`sample_indices`], so the bins can be built in a first pass over
+    /// the data without loading all of it.
+    ///
+    /// The bins are constructed with the binning parameters and categorical
+    /// features of `params`, which should also be given to [`sample_indices`].
+    ///
+    /// Example
+    /// ```
+    /// use lightgbm::{sample_indices, DatasetBuilder, DatasetParamsBuilder, RowMetadata};
+    ///
+    /// let rows = vec![vec![1.0, 0.1], vec![0.7, 0.4], vec![0.9, 0.8], vec![0.2, 0.2]];
+    /// let labels = vec![0.0, 0.0, 1.0, 1.0];
+    /// let params = DatasetParamsBuilder::default()
+    ///     .max_bin(15)
+    ///     .bin_construct_sample_cnt(1000)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // first pass: collect the sampled rows
+    /// let sample = sample_indices(rows.len(), &params)
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .flat_map(|i| rows[i as usize].clone())
+    ///     .collect::<Vec<f64>>();
+    /// let mut builder = DatasetBuilder::from_sample(&sample, 2, rows.len(), &params).unwrap();
+    ///
+    /// // second pass: push all rows
+    /// for (row, label) in rows.iter().zip(labels.iter()) {
+    ///     builder.push_rows(row, RowMetadata::new(&[*label])).unwrap();
+    /// }
+    /// let dataset = builder.finish().unwrap();
+    /// ```
+    pub fn from_sample<T: DataType>(
+        sample: &[T],
+        num_col: usize,
+        num_total_row: usize,
+        params: &DatasetParams,
+    ) -> Result<Self> {
+        let num_sample_row = sample.len().checked_div(num_col).unwrap_or(0);
+        if num_sample_row == 0 || num_sample_row * num_col != sample.len() {
+            return Err(Error::new(format!(
+                "length of sample ({}) must be a non-zero multiple of the number of columns ({})",
+                sample.len(),
+                num_col
+            )));
+        }
+
+        // LightGBM expects the non-zero values of each column and their rows
+        let mut values = vec![Vec::new(); num_col];
+        let mut rows = vec![Vec::new(); num_col];
+        for (i, &value) in sample.iter().enumerate() {
+            let value: f64 = value.into();
+            if value.abs() > ZERO_THRESHOLD || value.is_nan() {
+                values[i % num_col].push(value);
+                rows[i % num_col].push((i / num_col) as c_int);
+            }
+        }
+        let mut values_ptr = values
+            .iter_mut()
+            .map(|v| v.as_mut_ptr())
+            .collect::<Vec<*mut c_double>>();
+        let mut rows_ptr = rows
+            .iter_mut()
+            .map(|r| r.as_mut_ptr())
+            .collect::<Vec<*mut c_int>>();
+        let num_per_col = rows.iter().map(|r| r.len() as c_int).collect::<Vec<_>>();
+        let params_str = CString::new(params.to_param_string()?).unwrap();
+        let mut handle = std::ptr::null_mut();
+
+        lgbm_call!(lightgbm_sys::LGBM_DatasetCreateFromSampledColumn(
+            values_ptr.as_mut_ptr(),
+            rows_ptr.as_mut_ptr(),
+            num_col as i32,
+            num_per_col.as_ptr(),
+            num_sample_row as i32,
+            num_total_row as i32,
+            num_total_row as i64,
+            params_str.as_ptr() as *const c_char,
+            &mut handle
+        ))?;
+
+        let dataset = Dataset::new(handle).with_feature_names(params)?;
+        Ok(Self::new(dataset, num_total_row, num_col))
+    }
+
+    fn new(dataset: Dataset, num_total_row: usize, num_col: usize) -> Self {
+        Self {
+            dataset,
+            num_total_row,
+            num_col,
+            num_pushed: 0,
+            layout: None,
+        }
+    }
+
+    /// Push a batch of dense rows in row-major order.
+    pub fn push_rows<T: DataType>(&mut self, data: &[T], metadata: RowMetadata) -> Result<()> {
+        let num_row = data.len().checked_div(self.num_col).unwrap_or(0);
+        if num_row * self.num_col != data.len() {
+            return Err(Error::new(format!(
+                "length of data ({}) is not a multiple of the number of columns ({})",
+                data.len(),
+                self.num_col
+            )));
+        }
+        self.init_batch(num_row, &metadata)?;
+
+        lgbm_call!(lightgbm_sys::LGBM_DatasetPushRowsWithMetadata(
+            self.dataset.handle,
+            data.as_ptr() as *const c_void,
+            T::DTYPE,
+            num_row as i32,
+            self.num_col as i32,
+            self.num_pushed as i32,
+            metadata.label.as_ptr(),
+            optional_ptr(metadata.weight),
+            optional_ptr(metadata.init_score),
+            optional_ptr(metadata.query),
+            0
+        ))?;
+        self.num_pushed += num_row;
+        Ok(())
+    }
+
+    /// Push a batch of sparse rows in CSR format, see [`Dataset::from_csr`].
+    pub fn push_csr<I: IndexType, T: DataType>(
+        &mut self,
+        indptr: &[I],
+        indices: &[i32],
+        data: &[T],
+        metadata: RowMetadata,
+    ) -> Result<()> {
+        check_compressed("indptr", indptr, indices, data, self.num_col)?;
+        let num_row = indptr.len() - 1;
+        self.init_batch(num_row, &metadata)?;
+
+        lgbm_call!(lightgbm_sys::LGBM_DatasetPushRowsByCSRWithMetadata(
+            self.dataset.handle,
+            indptr.as_ptr() as *const c_void,
+            I::DTYPE,
+            indices.as_ptr(),
+            data.as_ptr() as *const c_void,
+            T::DTYPE,
+            indptr.len() as i64,
+            data.len() as i64,
+            self.num_pushed as i64,
+            metadata.label.as_ptr(),
+            optional_ptr(metadata.weight),
+            optional_ptr(metadata.init_score),
+            optional_ptr(metadata.query),
+            0
+        ))?;
+        self.num_pushed += num_row;
+        Ok(())
+    }
+
+    /// Number of rows pushed so far.
+    pub fn num_pushed(&self) -> usize {
+        self.num_pushed
+    }
+
+    /// Finish construction, all rows must have been pushed.
+    pub fn finish(self) -> Result<Dataset> {
+        if self.num_pushed != self.num_total_row {
+            return Err(Error::new(format!(
+                "pushed {} of {} rows",
+                self.num_pushed, self.num_total_row
+            )));
+        }
+        lgbm_call!(lightgbm_sys::LGBM_DatasetMarkFinished(self.dataset.handle))?;
+        Ok(self.dataset)
+    }
+
+    /// Validate a batch of `num_row` rows, initializing streaming on the first one.
+    fn init_batch(&mut self, num_row: usize, metadata: &RowMetadata) -> Result<()> {
+        if self.num_pushed + num_row > self.num_total_row {
+            return Err(Error::new(format!(
+                "cannot push {} rows, {} of {} rows are left",
+                num_row,
+                self.num_total_row - self.num_pushed,
+                self.num_total_row
+            )));
+        }
+        check_len("label", metadata.label.len(), num_row)?;
+        if let Some(weight) = metadata.weight {
+            check_len("weight", weight.len(), num_row)?;
+        }
+        if let Some(query) = metadata.query {
+            check_len("query", query.len(), num_row)?;
+        }
+        let num_class = match metadata.init_score {
+            Some(init_score) if num_row > 0 => init_score.len() / num_row,
+            _ => 0,
+        };
+        if let Some(init_score) = metadata.init_score {
+            check_len("init_score", init_score.len(), num_row * num_class.max(1))?;
+        }
+
+        let layout = Layout {
+            has_weight: metadata.weight.is_some(),
+            has_query: metadata.query.is_some(),
+            num_class,
+        };
+        match self.layout {
+            Some(previous) if previous != layout => Err(Error::new(
+                "weight, init_score and query must be given for all batches or none",
+            )),
+            Some(_) => Ok(()),
+            None => {
+                lgbm_call!(lightgbm_sys::LGBM_DatasetInitStreaming(
+                    self.dataset.handle,
+                    layout.has_weight as i32,
+                    (layout.num_class > 0) as i32,
+                    layout.has_query as i32,
+                    layout.num_class.max(1) as i32,
+                    1,
+                    -1
+                ))?;
+                self.layout = Some(layout);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Values with a smaller magnitude are treated as zero by LightGBM.
+const ZERO_THRESHOLD: f64 = 1e-35;
+
+/// Number of rows LightGBM samples out of `num_total_row` to build the bins,
+/// at most `bin_construct_sample_cnt` of `params`.
+pub fn sample_count(num_total_row: usize, params: &DatasetParams) -> Result<usize> {
+    let params_str = CString::new(params.to_param_string()?).unwrap();
+    let mut count = 0;
+    lgbm_call!(lightgbm_sys::LGBM_GetSampleCount(
+        num_total_row as i32,
+        params_str.as_ptr() as *const c_char,
+        &mut count
+    ))?;
+    Ok(count as usize)
+}
+
+/// Sorted indices of the rows LightGBM samples out of `num_total_row` to build the bins.
+///
+/// Pass the same `params` as to [`DatasetBuilder::from_sample`].
+pub fn sample_indices(num_total_row: usize, params: &DatasetParams) -> Result<Vec<i32>> {
+    let params_str = CString::new(params.to_param_string()?).unwrap();
+    let mut indices = vec![0_i32; sample_count(num_total_row, params)?];
+    let mut out_len = 0;
+    lgbm_call!(lightgbm_sys::LGBM_SampleIndices(
+        num_total_row as i32,
+        params_str.as_ptr() as *const c_char,
+        indices.as_mut_ptr() as *mut c_void,
+        &mut out_len
+    ))?;
+    indices.truncate(out_len as usize);
+    Ok(indices)
+}
+
+fn check_len(field_name: &str, len: usize, num_row: usize) -> Result<()> {
+    if len != num_row {
+        return Err(Error::new(format!(
+            "length of {} ({}) does not match the number of rows in the batch ({})",
+            field_name, len, num_row
+        )));
+    }
+    Ok(())
+}
+
+fn optional_ptr<T>(values: Option<&[T]>) -> *const T {
+    values.map_or(std::ptr::null(), |v| v.as_ptr())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Booster, DatasetParamsBuilder, TrainParamsBuilder};
+
+    fn _reference() -> Dataset {
+        let data = vec![
+            1.0, 0.1, 0.2, 0.1, 0.7, 0.4, 0.5, 0.1, 0.9, 0.8, 0.5, 0.1, 0.2, 0.2, 0.8, 0.7, 0.1,
+            0.7, 1.0, 0.9,
+        ];
+        Dataset::from_vec(&data, &[0.0, 0.0, 0.0, 1.0, 1.0], 4).unwrap()
+    }
+
+    #[test]
+    fn push_rows() {
+        let reference = _reference();
+        let params = DatasetParamsBuilder::default()
+            .feature_names(vec![
+                String::from("a"),
+                String::from("b"),
+                String::from("c"),
+                String::from("d"),
+            ])
+            .build()
+            .unwrap();
+        let mut builder = DatasetBuilder::from_reference(&reference, 5, &params).unwrap();
+        let batch: Vec<f64> = vec![1.0, 0.1, 0.2, 0.1, 0.7, 0.4, 0.5, 0.1];
+        let metadata = RowMetadata {
+            weight: Some(&[1.0, 2.0]),
+            ..RowMetadata::new(&[0.0, 0.0])
+        };
+        builder.push_rows(&batch, metadata).unwrap();
+        assert_eq!(builder.num_pushed(), 2);
+
+        // sparse rows [0.9, 0.8, 0.5, 0.0], [0.2, 0.0, 0.8, 0.7], [0.0, 0.7, 1.0, 0.9]
+        let indptr: Vec<i32> = vec![0, 3, 6, 9];
+        let indices = vec![0, 1, 2, 0, 2, 3, 1, 2, 3];
+        let data: Vec<f32> = vec![0.9, 0.8, 0.5, 0.2, 0.8, 0.7, 0.7, 1.0, 0.9];
+        let metadata = RowMetadata {
+            weight: Some(&[1.0, 1.0, 0.5]),
+            ..RowMetadata::new(&[0.0, 1.0, 1.0])
+        };
+        builder
+            .push_csr(&indptr, &indices, &data, metadata)
+            .unwrap();
+
+        let dataset = builder.finish().unwrap();
+        assert_eq!(dataset.label().unwrap(), vec![0.0, 0.0, 0.0, 1.0, 1.0]);
+        assert_eq!(dataset.weight().unwrap(), vec![1.0, 2.0, 1.0, 1.0, 0.5]);
+        assert_eq!(dataset.feature_names().unwrap(), vec!["a", "b", "c", "d"]);
+
+        let params = TrainParamsBuilder::default()
+            .num_iterations(3)
+            .param("min_data_in_leaf", 1)
+            .build()
+            .unwrap();
+        assert!(Booster::train(dataset, &params).is_ok());
+    }
+
+    #[test]
+    fn sample() {
+        let params = DatasetParams::default();
+        assert_eq!(sample_count(100, &params).unwrap(), 100);
+        let indices = sample_indices(1_000_000, &params).unwrap();
+        assert_eq!(indices.len(), sample_count(1_000_000, &params).unwrap());
+        assert!(indices.windows(2).all(|w| w[0] < w[1]));
+        assert!(indices.iter().all(|&i| (0..1_000_000).contains(&i)));
+
+        let params = DatasetParamsBuilder::default()
+            .bin_construct_sample_cnt(1000)
+            .build()
+            .unwrap();
+        assert_eq!(sample_count(1_000_000, &params).unwrap(), 1000);
+        assert_eq!(sample_indices(1_000_000, &params).unwrap().len(), 1000);
+    }
+
+    #[test]
+    fn from_sample() {
+        let sample = vec![
+            1.0_f32, 0.1, 0.0, 0.7, 0.4, 0.5, 0.9, 0.0, 0.5, 0.2, 0.2, 0.8,
+        ];
+        let params = DatasetParamsBuilder::default()
+            .feature_names(vec![
+                String::from("a"),
+                String::from("b"),
+                String::from("c"),
+            ])
+            .max_bin(2)
+            .build()
+            .unwrap();
+        let mut builder = DatasetBuilder::from_sample(&sample, 3, 6, &params).unwrap();
+        builder
+            .push_rows(&sample, RowMetadata::new(&[0.0, 0.0, 1.0, 1.0]))
+            .unwrap();
+        builder
+            .push_rows(&sample[..6], RowMetadata::new(&[0.0, 1.0]))
+            .unwrap();
+        let dataset = builder.finish().unwrap();
+        assert_eq!(dataset.label().unwrap().len(), 6);
+        assert_eq!(dataset.feature_names().unwrap(), vec!["a", "b", "c"]);
+
+        let params = DatasetParams::default();
+        assert!(DatasetBuilder::from_sample(&sample, 5, 6, &params).is_err());
+        assert!(DatasetBuilder::from_sample::<f64>(&[], 3, 6, &params).is_err());
+    }
+
+    #[test]
+    fn from_serialized_reference() {
+        let reference = _reference().serialize_reference().unwrap();
+        assert!(!reference.is_empty());
+
+        let params = DatasetParams::default();
+        let mut builder =
+            DatasetBuilder::from_serialized_reference(&reference, 2, &params).unwrap();
+        let rows: Vec<f32> = vec![0.9, 0.8, 0.5, 0.1, 0.2, 0.2, 0.8, 0.7];
+        builder
+            .push_rows(&rows, RowMetadata::new(&[0.0, 1.0]))
+            .unwrap();
+        let dataset = builder.finish().unwrap();
+        assert_eq!(dataset.label().unwrap(), vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn push_rows_invalid() {
+        let reference = _reference();
+        let params = DatasetParams::default();
+        let mut builder = DatasetBuilder::from_reference(&reference, 2, &params).unwrap();
+        let row: Vec<f32> = vec![1.0, 0.1, 0.2, 0.1];
+        assert!(builder
+            .push_rows(&row[..3], RowMetadata::new(&[0.0]))
+            .is_err());
+        assert!(builder.push_rows(&row, RowMetadata::new(&[])).is_err());
+
+        let metadata = RowMetadata {
+            weight: Some(&[1.0]),
+            ..RowMetadata::new(&[0.0])
+        };
+        builder.push_rows(&row, metadata).unwrap();
+        assert!(builder.push_rows(&row, RowMetadata::new(&[0.0])).is_err());
+        builder.push_rows(&row, metadata).unwrap();
+        assert!(builder.push_rows(&row, metadata).is_err());
+        assert!(builder.finish().is_ok());
+
+        let builder = DatasetBuilder::from_reference(&reference, 2, &params).unwrap();
+        assert!(builder.finish().is_err());
+    }
+}
@@ -0,0 +1,511 @@
+//! Options and results for training a `Booster`.
+
+use std::ffi::CString;
+
+use libc::c_char;
+use lightgbm_sys;
+use serde_json::Value;
+
+use crate::{
+    Booster, Dataset, Error, EvalResult, Metric, Objective, ObjectiveType, Result, TrainParams,
+    TrainingCallback,
+};
+
+/// Additional inputs for [`Booster::train_with_options`](crate::Booster::train_with_options).
+///
+/// Example
+/// ```
+/// use lightgbm::{Dataset, TrainOptions};
+///
+/// let train = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train").unwrap();
+/// let valid = Dataset::from_file_with_reference(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.test", &train).unwrap();
+/// let options = TrainOptions::new().valid_set("valid", &valid);
+/// ```
+#[derive(Default)]
+pub struct TrainOptions<'a> {
+    pub(crate) valid_sets: Vec<(String, &'a Dataset)>,
+    pub(crate) objective: Option<Box<dyn Objective + 'a>>,
+    pub(crate) metrics: Vec<Box<dyn Metric + 'a>>,
+    pub(crate) train_name: Option<String>,
+    pub(crate) callbacks: Vec<Box<dyn TrainingCallback + 'a>>,
+}
+
+impl<'a> TrainOptions<'a> {
+    /// Create empty options, equivalent to plain [`Booster::train`](crate::Booster::train).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a named validation dataset, evaluated after every iteration.
+    ///
+    /// The dataset must be created with the training dataset as its reference,
+    /// so both share the same bins.
+    pub fn valid_set(mut self, name: &str, dataset: &'a Dataset) -> Self {
+        self.valid_sets.push((String::from(name), dataset));
+        self
+    }
+
+    /// Train with a custom objective instead of the `objective` parameter.
+    ///
+    /// The `objective` parameter is replaced by `none`, so predictions of the
+    /// trained model are raw scores.
+    pub fn objective<O: Objective + 'a>(mut self, objective: O) -> Self {
+        self.objective = Some(Box::new(objective));
+        self
+    }
+
+    /// Add a custom metric, evaluated alongside the `metric` parameter.
+    pub fn metric<M: Metric + 'a>(mut self, metric: M) -> Self {
+        self.metrics.push(Box::new(metric));
+        self
+    }
+
+    /// Also evaluate the metrics on the training data, recorded under `name`.
+    ///
+    /// Training metrics are not used for early stopping.
+    pub fn eval_train(mut self, name: &str) -> Self {
+        self.train_name = Some(String::from(name));
+        self
+    }
+
+    /// Add a callback, invoked in insertion order before and after each iteration.
+    pub fn callback<C: TrainingCallback + 'a>(mut self, callback: C) -> Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+}
+
+/// Per-iteration metric values recorded during training.
+///
+/// Values are grouped by dataset name and then by metric name, in the order
+/// the datasets were added and the metrics were reported by LightGBM.
+///
+/// Example
+/// ```
+/// extern crate serde_json;
+/// use lightgbm::{Booster, Dataset, TrainOptions};
+/// use serde_json::json;
+///
+/// let train = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train").unwrap();
+/// let valid = Dataset::from_file_with_reference(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.test", &train).unwrap();
+/// let params = json!{
+///    {
+///         "num_iterations": 3,
+///         "objective": "binary",
+///         "metric": "auc"
+///     }
+/// };
+/// let options = TrainOptions::new().valid_set("valid", &valid);
+/// let bst = Booster::train_with_options(train, &params, options).unwrap();
+/// let auc = bst.eval_history().get("valid", "auc").unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EvalHistory {
+    datasets: Vec<(String, MetricHistory)>,
+}
+
+/// Metric name and its per-iteration values.
+type MetricHistory = Vec<(String, Vec<f64>)>;
+
+impl EvalHistory {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a metric value for the current iteration.
+    pub(crate) fn push(&mut self, dataset: &str, metric: &str, value: f64) {
+        let metrics = match self.datasets.iter().position(|(name, _)| name == dataset) {
+            Some(i) => &mut self.datasets[i].1,
+            None => {
+                self.datasets.push((String::from(dataset), Vec::new()));
+                &mut self.datasets.last_mut().unwrap().1
+            }
+        };
+        match metrics.iter().position(|(name, _)| name == metric) {
+            Some(i) => metrics[i].1.push(value),
+            None => metrics.push((String::from(metric), vec![value])),
+        }
+    }
+
+    /// Get the values of `metric` on `dataset`, one per iteration.
+    pub fn get(&self, dataset: &str, metric: &str) -> Option<&[f64]> {
+        self.datasets
+            .iter()
+            .find(|(name, _)| name == dataset)
+            .and_then(|(_, metrics)| metrics.iter().find(|(name, _)| name == metric))
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// Get the names of the evaluated datasets.
+    pub fn datasets(&self) -> Vec<&str> {
+        self.datasets
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Get the names of the metrics evaluated on `dataset`.
+    pub fn metrics(&self, dataset: &str) -> Vec<&str> {
+        self.datasets
+            .iter()
+            .find(|(name, _)| name == dataset)
+            .map(|(_, metrics)| metrics.iter().map(|(name, _)| name.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if nothing was evaluated.
+    pub fn is_empty(&self) -> bool {
+        self.datasets.is_empty()
+    }
+}
+
+/// A booster being trained one iteration at a time, with its evaluation datasets and metrics.
+pub(crate) struct Trainer<'a> {
+    pub(crate) booster: Booster,
+    objective: Option<Box<dyn Objective + 'a>>,
+    metrics: Vec<Box<dyn Metric + 'a>>,
+    labels: Vec<f32>,
+    num_eval: usize,
+    /// Name and `higher_better` of the built-in, then the custom metrics.
+    metric_names: Vec<(String, bool)>,
+    /// Index, name, labels and weights of the evaluated datasets. Index 0 is
+    /// the training data, validation data follows in insertion order.
+    eval_sets: Vec<(i32, String, Vec<f32>, Vec<f32>)>,
+    has_train_eval: bool,
+}
+
+impl<'a> Trainer<'a> {
+    /// Create the booster and add the validation datasets.
+    pub(crate) fn new(
+        dataset: &Dataset,
+        mut parameter: TrainParams,
+        valid_sets: &[(String, &Dataset)],
+        train_name: Option<String>,
+        objective: Option<Box<dyn Objective + 'a>>,
+        metrics: Vec<Box<dyn Metric + 'a>>,
+    ) -> Result<Self> {
+        // gradients come from the custom objective, LightGBM must not compute its own
+        if objective.is_some() {
+            parameter.objective = Some(ObjectiveType::Custom);
+        }
+        if train_name.is_some() {
+            parameter.extra.insert(
+                String::from("is_provide_training_metric"),
+                Value::from(true),
+            );
+        }
+
+        // exchange params {"x": "y", "z": [1, 2]} => "x=y z=1,2"
+        let params_cstring = CString::new(parameter.to_param_string()?).unwrap();
+
+        let mut handle = std::ptr::null_mut();
+        lgbm_call!(lightgbm_sys::LGBM_BoosterCreate(
+            dataset.handle,
+            params_cstring.as_ptr() as *const c_char,
+            &mut handle
+        ))?;
+        let booster = Booster::new(handle);
+
+        for (_, valid) in valid_sets.iter() {
+            lgbm_call!(lightgbm_sys::LGBM_BoosterAddValidData(
+                booster.handle,
+                valid.handle
+            ))?;
+        }
+        let has_eval = train_name.is_some() || !valid_sets.is_empty();
+        let eval_names = if has_eval {
+            booster.eval_names()?
+        } else {
+            Vec::new()
+        };
+        let metric_names = eval_names
+            .iter()
+            .map(|name| (name.clone(), is_higher_better(name)))
+            .chain(
+                metrics
+                    .iter()
+                    .map(|metric| (String::from(metric.name()), metric.is_higher_better())),
+            )
+            .collect::<Vec<_>>();
+
+        let needs_train_label =
+            objective.is_some() || (train_name.is_some() && !metrics.is_empty());
+        let labels = if needs_train_label {
+            dataset.label()?
+        } else {
+            Vec::new()
+        };
+
+        let mut eval_sets = Vec::new();
+        let has_train_eval = train_name.is_some();
+        if let Some(name) = train_name {
+            let weights = if metrics.is_empty() {
+                Vec::new()
+            } else {
+                dataset.weight()?
+            };
+            eval_sets.push((0, name, labels.clone(), weights));
+        }
+        for (i, (name, valid)) in valid_sets.iter().enumerate() {
+            let (labels, weights) = if metrics.is_empty() {
+                (Vec::new(), Vec::new())
+            } else {
+                (valid.label()?, valid.weight()?)
+            };
+            eval_sets.push((i as i32 + 1, name.clone(), labels, weights));
+        }
+
+        Ok(Self {
+            booster,
+            objective,
+            metrics,
+            labels,
+            num_eval: eval_names.len(),
+            metric_names,
+            eval_sets,
+            has_train_eval,
+        })
+    }
+
+    /// Whether any built-in or custom metric is evaluated.
+    pub(crate) fn has_metrics(&self) -> bool {
+        !self.metric_names.is_empty()
+    }
+
+    /// Train one iteration, returns `true` if LightGBM cannot find any more splits.
+    pub(crate) fn update(&mut self) -> Result<bool> {
+        let mut is_finished: i32 = 0;
+        match self.objective.as_mut() {
+            Some(objective) => {
+                let predictions = self.booster.inner_predict(0)?;
+                let (grad, hess) = objective.gradients(&predictions, &self.labels);
+                if grad.len() != predictions.len() || hess.len() != predictions.len() {
+                    return Err(Error::new(format!(
+                        "custom objective returned {} gradients and {} hessians, expected {}",
+                        grad.len(),
+                        hess.len(),
+                        predictions.len()
+                    )));
+                }
+                lgbm_call!(lightgbm_sys::LGBM_BoosterUpdateOneIterCustom(
+                    self.booster.handle,
+                    grad.as_ptr(),
+                    hess.as_ptr(),
+                    &mut is_finished
+                ))?;
+            }
+            None => {
+                lgbm_call!(lightgbm_sys::LGBM_BoosterUpdateOneIter(
+                    self.booster.handle,
+                    &mut is_finished
+                ))?;
+            }
+        }
+        Ok(is_finished == 1)
+    }
+
+    /// Evaluate all metrics on all evaluated datasets and record them in the eval history.
+    pub(crate) fn eval(&mut self) -> Result<Vec<EvalResult>> {
+        let mut results = Vec::new();
+        for (data_idx, name, labels, weights) in self.eval_sets.iter() {
+            let mut values = self.booster.eval(*data_idx, self.num_eval)?;
+            if !self.metrics.is_empty() {
+                let predictions = self.booster.inner_predict(*data_idx)?;
+                let weights = if weights.is_empty() {
+                    None
+                } else {
+                    Some(weights.as_slice())
+                };
+                values.extend(
+                    self.metrics
+                        .iter()
+                        .map(|metric| metric.eval(labels, weights, &predictions)),
+                );
+            }
+            for ((metric, higher_better), value) in self.metric_names.iter().zip(values) {
+                self.booster.eval_history.push(name, metric, value);
+                results.push(EvalResult {
+                    dataset: name.clone(),
+                    metric: metric.clone(),
+                    value,
+                    higher_better: *higher_better,
+                });
+            }
+        }
+        Ok(results)
+    }
+
+    /// The results of [`Trainer::eval`] on the validation datasets.
+    pub(crate) fn valid_results<'r>(&self, results: &'r [EvalResult]) -> &'r [EvalResult] {
+        if self.has_train_eval {
+            &results[self.metric_names.len().min(results.len())..]
+        } else {
+            results
+        }
+    }
+}
+
+/// Early stopping on the validation metrics, configured from the
+/// `early_stopping_round`, `first_metric_only` and `early_stopping_min_delta` parameters.
+pub(crate) struct EarlyStopping {
+    stopping_rounds: i32,
+    first_metric_only: bool,
+    min_delta: f64,
+    best: Vec<(i32, f64)>,
+}
+
+impl EarlyStopping {
+    /// Read the early stopping settings, returns `None` if early stopping is disabled.
+    pub(crate) fn from_params(parameter: &TrainParams) -> Result<Option<Self>> {
+        let stopping_rounds = match parameter.early_stopping_round {
+            Some(rounds) if rounds > 0 => rounds,
+            _ => return Ok(None),
+        };
+        let min_delta = match parameter.extra.get("early_stopping_min_delta") {
+            None => 0.0,
+            Some(v) => v.as_f64().ok_or_else(|| {
+                Error::new("parameter 'early_stopping_min_delta' must be a number")
+            })?,
+        };
+        Ok(Some(Self {
+            stopping_rounds,
+            first_metric_only: parameter.first_metric_only.unwrap_or(false),
+            min_delta,
+            best: Vec::new(),
+        }))
+    }
+
+    /// Record the validation `scores` of `iteration` as `(metric, score, higher_better)`.
+    ///
+    /// Returns the best iteration and score of the metric that stopped improving,
+    /// or `None` if training should continue.
+    pub(crate) fn update(
+        &mut self,
+        iteration: i32,
+        scores: &[(&str, f64, bool)],
+    ) -> Option<(i32, f64)> {
+        if self.best.is_empty() {
+            self.best = scores
+                .iter()
+                .map(|(_, score, _)| (iteration, *score))
+                .collect();
+            return None;
+        }
+        let first_metric = scores.first().map(|(metric, _, _)| *metric);
+        for (i, (metric, score, higher_better)) in scores.iter().enumerate() {
+            if self.first_metric_only && Some(*metric) != first_metric {
+                continue;
+            }
+            let (best_iteration, best_score) = self.best[i];
+            let improved = if *higher_better {
+                *score - self.min_delta > best_score
+            } else {
+                *score + self.min_delta < best_score
+            };
+            if improved {
+                self.best[i] = (iteration, *score);
+            } else if iteration - best_iteration >= self.stopping_rounds {
+                return Some(self.best[i]);
+            }
+        }
+        None
+    }
+
+    /// Best iteration and score of the first checked metric.
+    pub(crate) fn best(&self) -> Option<(i32, f64)> {
+        self.best.first().cloned()
+    }
+}
+
+/// Whether a larger value of the built-in LightGBM `metric` is better.
+pub(crate) fn is_higher_better(metric: &str) -> bool {
+    ["auc", "ndcg@", "map@", "average_precision"]
+        .iter()
+        .any(|prefix| metric.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eval_history() {
+        let mut history = EvalHistory::new();
+        assert!(history.is_empty());
+        history.push("valid", "auc", 0.5);
+        history.push("valid", "binary_logloss", 0.7);
+        history.push("valid", "auc", 0.6);
+        history.push("train", "auc", 0.8);
+
+        assert_eq!(history.get("valid", "auc"), Some(&[0.5, 0.6][..]));
+        assert_eq!(history.get("valid", "binary_logloss"), Some(&[0.7][..]));
+        assert_eq!(history.get("valid", "l2"), None);
+        assert_eq!(history.datasets(), vec!["valid", "train"]);
+        assert_eq!(history.metrics("valid"), vec!["auc", "binary_logloss"]);
+        assert!(history.metrics("test").is_empty());
+    }
+
+    fn _params(value: serde_json::Value) -> TrainParams {
+        TrainParams::from_json(&value).unwrap()
+    }
+
+    #[test]
+    fn early_stopping_from_params() {
+        use serde_json::json;
+        let params = _params(json! {{"objective": "binary"}});
+        assert!(EarlyStopping::from_params(&params).unwrap().is_none());
+
+        let params = _params(json! {{"early_stopping_rounds": 0}});
+        assert!(EarlyStopping::from_params(&params).unwrap().is_none());
+
+        let params = _params(json! {{"early_stopping_min_delta": "0.1", "early_stopping": 5}});
+        assert!(EarlyStopping::from_params(&params).is_err());
+
+        let params = _params(json! {{"n_iter_no_change": 5, "first_metric_only": true}});
+        let early_stopping = EarlyStopping::from_params(&params).unwrap().unwrap();
+        assert_eq!(early_stopping.stopping_rounds, 5);
+        assert!(early_stopping.first_metric_only);
+    }
+
+    #[test]
+    fn early_stopping_update() {
+        let params = _params(serde_json::json! {{"early_stopping_round": 2}});
+        let mut early_stopping = EarlyStopping::from_params(&params).unwrap().unwrap();
+        assert_eq!(
+            early_stopping.update(1, &[("auc", 0.6, true), ("l2", 0.5, false)]),
+            None
+        );
+        assert_eq!(
+            early_stopping.update(2, &[("auc", 0.7, true), ("l2", 0.4, false)]),
+            None
+        );
+        assert_eq!(
+            early_stopping.update(3, &[("auc", 0.8, true), ("l2", 0.45, false)]),
+            None
+        );
+        assert_eq!(
+            early_stopping.update(4, &[("auc", 0.9, true), ("l2", 0.41, false)]),
+            Some((2, 0.4))
+        );
+        assert_eq!(early_stopping.best(), Some((4, 0.9)));
+    }
+
+    #[test]
+    fn early_stopping_first_metric_only() {
+        let params =
+            _params(serde_json::json! {{"early_stopping_round": 1, "first_metric_only": true}});
+        let mut early_stopping = EarlyStopping::from_params(&params).unwrap().unwrap();
+        assert_eq!(
+            early_stopping.update(1, &[("auc", 0.6, true), ("l2", 0.5, false)]),
+            None
+        );
+        assert_eq!(
+            early_stopping.update(2, &[("auc", 0.7, true), ("l2", 0.6, false)]),
+            None
+        );
+        assert_eq!(
+            early_stopping.update(3, &[("auc", 0.7, true), ("l2", 0.4, false)]),
+            Some((2, 0.7))
+        );
+    }
+}
@@ -0,0 +1,612 @@
+//! Hyperparameter search with cross-validation.
+
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use serde_json::Value;
+
+use crate::cv::{cv_on_folds, fold_datasets};
+use crate::rng::Rng;
+use crate::train::is_higher_better;
+use crate::{Dataset, Error, Folds, Result, TrainParams};
+
+/// Largest number of candidates a grid search may generate.
+const MAX_GRID_SIZE: usize = 100_000;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Distribution {
+    Values(Vec<Value>),
+    IntRange(i64, i64),
+    FloatRange(f64, f64),
+    LogRange(f64, f64),
+}
+
+impl Distribution {
+    fn sample(&self, rng: &mut Rng) -> Value {
+        match self {
+            Distribution::Values(values) => values[rng.below(values.len())].clone(),
+            Distribution::IntRange(low, high) => {
+                let span = int_span(*low, *high).expect("int range is validated");
+                Value::from(low + rng.below(span) as i64)
+            }
+            Distribution::FloatRange(low, high) => Value::from(low + rng.next_f64() * (high - low)),
+            Distribution::LogRange(low, high) => {
+                let (low, high) = (low.ln(), high.ln());
+                Value::from((low + rng.next_f64() * (high - low)).exp())
+            }
+        }
+    }
+
+    /// Number of values on a grid, `None` for continuous ranges.
+    fn grid_size(&self) -> Option<usize> {
+        match self {
+            Distribution::Values(values) => Some(values.len()),
+            Distribution::IntRange(low, high) => int_span(*low, *high),
+            Distribution::FloatRange(..) | Distribution::LogRange(..) => None,
+        }
+    }
+
+    fn grid(&self) -> Option<Vec<Value>> {
+        match self {
+            Distribution::Values(values) => Some(values.clone()),
+            Distribution::IntRange(low, high) => Some((*low..=*high).map(Value::from).collect()),
+            Distribution::FloatRange(..) | Distribution::LogRange(..) => None,
+        }
+    }
+}
+
+/// Values to search for each tuned parameter.
+///
+/// Parameters are given by LightGBM name or alias, and are checked like
+/// [`TrainParams::set`] when the search runs.
+///
+/// Example
+/// ```
+/// use lightgbm::SearchSpace;
+///
+/// let space = SearchSpace::new()
+///     .values("boosting", vec!["gbdt", "dart"])
+///     .int_range("num_leaves", 15, 63)
+///     .log_range("learning_rate", 0.01, 0.3)
+///     .float_range("feature_fraction", 0.5, 1.0);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchSpace {
+    params: Vec<(String, Distribution)>,
+}
+
+impl SearchSpace {
+    /// Create an empty search space.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Search the given values of a parameter.
+    pub fn values<V: Into<Value>>(mut self, name: &str, values: Vec<V>) -> Self {
+        let values = values.into_iter().map(Into::into).collect();
+        self.params
+            .push((String::from(name), Distribution::Values(values)));
+        self
+    }
+
+    /// Search the integers from `low` to `high`, both included.
+    ///
+    /// The number of integers in the range must fit in a `usize`.
+    pub fn int_range(mut self, name: &str, low: i64, high: i64) -> Self {
+        self.params
+            .push((String::from(name), Distribution::IntRange(low, high)));
+        self
+    }
+
+    /// Search uniformly between `low` and `high`, random search only.
+    pub fn float_range(mut self, name: &str, low: f64, high: f64) -> Self {
+        self.params
+            .push((String::from(name), Distribution::FloatRange(low, high)));
+        self
+    }
+
+    /// Search log-uniformly between `low` and `high`, random search only.
+    pub fn log_range(mut self, name: &str, low: f64, high: f64) -> Self {
+        self.params
+            .push((String::from(name), Distribution::LogRange(low, high)));
+        self
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (name, distribution) in self.params.iter() {
+            let valid = match distribution {
+                Distribution::Values(values) => !values.is_empty(),
+                Distribution::IntRange(low, high) => low <= high,
+                Distribution::FloatRange(low, high) => low <= high,
+                Distribution::LogRange(low, high) => *low > 0.0 && low <= high,
+            };
+            if !valid {
+                return Err(Error::new(format!(
+                    "search range of parameter '{}' is empty",
+                    name
+                )));
+            }
+            if let Distribution::IntRange(low, high) = distribution {
+                if int_span(*low, *high).is_none() {
+                    return Err(Error::new(format!(
+                        "search range of parameter '{}' is too wide",
+                        name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Every combination of the values, failing for continuous ranges and
+    /// grids of more than `MAX_GRID_SIZE` candidates.
+    fn grid(&self) -> Result<Vec<Vec<(String, Value)>>> {
+        let mut grid_size = 1_usize;
+        for (name, distribution) in self.params.iter() {
+            let size = distribution.grid_size().ok_or_else(|| {
+                Error::new(format!(
+                    "parameter '{}' has a continuous range and cannot be searched on a grid",
+                    name
+                ))
+            })?;
+            grid_size = grid_size
+                .checked_mul(size)
+                .filter(|&n| n <= MAX_GRID_SIZE)
+                .ok_or_else(|| {
+                    Error::new(format!(
+                        "grid has more than {} candidates, use random search",
+                        MAX_GRID_SIZE
+                    ))
+                })?;
+        }
+
+        let mut candidates = vec![Vec::new()];
+        for (name, distribution) in self.params.iter() {
+            let values = distribution.grid().unwrap_or_default();
+            candidates = candidates
+                .into_iter()
+                .flat_map(|candidate| {
+                    values.iter().map(move |value| {
+                        let mut candidate = candidate.clone();
+                        candidate.push((name.clone(), value.clone()));
+                        candidate
+                    })
+                })
+                .collect();
+        }
+        Ok(candidates)
+    }
+
+    fn sample(&self, num_candidates: usize, seed: u64) -> Vec<Vec<(String, Value)>> {
+        let mut rng = Rng::new(seed);
+        (0..num_candidates)
+            .map(|_| {
+                self.params
+                    .iter()
+                    .map(|(name, distribution)| (name.clone(), distribution.sample(&mut rng)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// A cross-validated candidate of a [`Tuner`] search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trial {
+    /// Parameters of the candidate, including the number of iterations it was trained for.
+    pub params: TrainParams,
+    /// Best mean validation value of the metric over the iterations.
+    pub score: f64,
+    /// Iteration of the best mean validation value.
+    pub best_iteration: i32,
+}
+
+/// Grid or random search of training parameters, scoring each candidate by cross-validation.
+///
+/// The dataset is split into folds once, and the binned folds are shared by
+/// all candidates. Dataset parameters such as `max_bin` are fixed when the
+/// dataset is constructed and cannot be tuned.
+///
+/// Example
+/// ```
+/// use lightgbm::{Dataset, Folds, MetricType, ObjectiveType, SearchSpace, TrainParamsBuilder, Tuner};
+///
+/// let dataset = Dataset::from_file(&"lightgbm-sys/lightgbm/examples/binary_classification/binary.train").unwrap();
+/// let params = TrainParamsBuilder::default()
+///     .objective(ObjectiveType::Binary)
+///     .metric(vec![MetricType::Auc])
+///     .num_iterations(20)
+///     .build()
+///     .unwrap();
+/// let space = SearchSpace::new()
+///     .int_range("num_leaves", 7, 63)
+///     .log_range("learning_rate", 0.01, 0.3);
+///
+/// let trials = Tuner::new(&dataset, params, space, Folds::stratified(3))
+///     .random(8, 42)
+///     .successive_halving(5, 2)
+///     .jobs(2)
+///     .run()
+///     .unwrap();
+/// let best = &trials[0];
+/// println!("auc {} with {:?}", best.score, best.params.to_json());
+/// ```
+pub struct Tuner<'a> {
+    dataset: &'a Dataset,
+    params: TrainParams,
+    space: SearchSpace,
+    folds: Folds,
+    random: Option<(usize, u64)>,
+    metric: Option<String>,
+    halving: Option<(i32, usize)>,
+    num_threads: Option<usize>,
+    jobs: usize,
+}
+
+impl<'a> Tuner<'a> {
+    /// Search `space` around the base `params` by grid search.
+    pub fn new(
+        dataset: &'a Dataset,
+        params: TrainParams,
+        space: SearchSpace,
+        folds: Folds,
+    ) -> Self {
+        Self {
+            dataset,
+            params,
+            space,
+            folds,
+            random: None,
+            metric: None,
+            halving: None,
+            num_threads: None,
+            jobs: 1,
+        }
+    }
+
+    /// Search `num_candidates` random candidates drawn with `seed` instead of the full grid.
+    pub fn random(mut self, num_candidates: usize, seed: u64) -> Self {
+        self.random = Some((num_candidates, seed));
+        self
+    }
+
+    /// Rank candidates by `metric`, the first metric of the parameters by default.
+    pub fn metric(mut self, metric: &str) -> Self {
+        self.metric = Some(String::from(metric));
+        self
+    }
+
+    /// Evaluate all candidates with `min_iterations` first, then repeatedly keep
+    /// the best `1 / factor` of them and multiply their iterations by `factor`,
+    /// up to the `num_iterations` of the base parameters.
+    pub fn successive_halving(mut self, min_iterations: i32, factor: usize) -> Self {
+        self.halving = Some((min_iterations, factor));
+        self
+    }
+
+    /// Total number of threads used by all running candidates, the
+    /// `num_threads` of the base parameters or all cores by default.
+    pub fn num_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = Some(num_threads);
+        self
+    }
+
+    /// Number of candidates evaluated in parallel, each with an equal share of
+    /// the threads. At most `num_threads` candidates run at once.
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Run the search, returning all candidates ranked best first.
+    ///
+    /// With successive halving, candidates evaluated with more iterations are
+    /// ranked before those eliminated earlier.
+    pub fn run(&self) -> Result<Vec<Trial>> {
+        self.space.validate()?;
+        let candidates = match self.random {
+            Some((num_candidates, seed)) => self.space.sample(num_candidates, seed),
+            None => self.space.grid()?,
+        };
+        let mut candidates = candidates
+            .into_iter()
+            .map(|values| {
+                let mut params = self.params.clone();
+                for (name, value) in values {
+                    params.set(&name, value)?;
+                }
+                Ok(params)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if candidates.is_empty() {
+            return Err(Error::new("no candidates to search"));
+        }
+        let num_threads = self
+            .num_threads
+            .or_else(|| {
+                self.params
+                    .num_threads
+                    .filter(|&n| n > 0)
+                    .map(|n| n as usize)
+            })
+            .unwrap_or_else(|| {
+                thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(1)
+            });
+        let (jobs, threads_per_job) = split_threads(num_threads, self.jobs);
+
+        let fold_data = SharedFolds(fold_datasets(self.dataset, &self.folds)?);
+        let max_iterations = self.params.num_iterations.unwrap_or(100);
+        let (mut iterations, factor) = match self.halving {
+            Some((min_iterations, factor)) if factor >= 2 && min_iterations > 0 => {
+                (min_iterations.min(max_iterations), factor)
+            }
+            Some(_) => return Err(Error::new(
+                "successive halving requires positive min_iterations and a factor of at least 2",
+            )),
+            None => (max_iterations, 1),
+        };
+
+        // rungs of trials, the last one evaluated with the most iterations
+        let mut rungs = Vec::new();
+        loop {
+            for params in candidates.iter_mut() {
+                params.num_iterations = Some(iterations);
+            }
+            let (mut trials, higher_better) = evaluate(
+                &fold_data,
+                candidates,
+                jobs,
+                threads_per_job,
+                self.metric.as_deref(),
+            )?;
+            trials.sort_by(|a, b| {
+                if higher_better {
+                    b.score.total_cmp(&a.score)
+                } else {
+                    a.score.total_cmp(&b.score)
+                }
+            });
+            if trials.len() <= 1 || iterations >= max_iterations || factor == 1 {
+                rungs.push(trials);
+                break;
+            }
+            let num_kept = trials.len().div_ceil(factor);
+            candidates = trials[..num_kept]
+                .iter()
+                .map(|t| t.params.clone())
+                .collect();
+            rungs.push(trials.split_off(num_kept));
+            iterations = iterations.saturating_mul(factor as i32).min(max_iterations);
+        }
+        Ok(rungs.into_iter().rev().flatten().collect())
+    }
+}
+
+/// Number of integers from `low` to `high`, both included, `None` if it
+/// does not fit in a `usize`.
+fn int_span(low: i64, high: i64) -> Option<usize> {
+    high.checked_sub(low)
+        .and_then(|n| n.checked_add(1))
+        .and_then(|n| usize::try_from(n).ok())
+}
+
+/// Number of parallel jobs and threads per job within a budget of `num_threads`.
+///
+/// Jobs are capped at the number of threads, so every job has at least one
+/// thread without exceeding the budget.
+fn split_threads(num_threads: usize, jobs: usize) -> (usize, usize) {
+    let num_threads = num_threads.max(1);
+    let jobs = jobs.min(num_threads).max(1);
+    (jobs, num_threads / jobs)
+}
+
+/// Fold datasets shared by the threads evaluating candidates.
+///
+/// `Dataset` is not `Sync` in general. The folds are never modified after
+/// they are split: workers only read their labels and pass them to
+/// `LGBM_BoosterCreate` and `LGBM_BoosterAddValidData`, which take them as
+/// `const Dataset*` while each booster allocates its own scores and training
+/// state. No `&mut` access is given out while they are shared.
+struct SharedFolds(Vec<(Dataset, Dataset)>);
+
+unsafe impl Sync for SharedFolds {}
+
+/// Cross-validate the candidates on `jobs` threads, also returning whether
+/// a higher score is better.
+fn evaluate(
+    fold_data: &SharedFolds,
+    candidates: Vec<TrainParams>,
+    jobs: usize,
+    threads_per_job: usize,
+    metric: Option<&str>,
+) -> Result<(Vec<Trial>, bool)> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..candidates.len()).map(|_| None).collect::<Vec<_>>());
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(candidates.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                if i >= candidates.len() {
+                    break;
+                }
+                let trial = evaluate_one(&fold_data.0, &candidates[i], threads_per_job, metric);
+                results.lock().unwrap()[i] = Some(trial);
+            });
+        }
+    });
+    let mut trials = Vec::with_capacity(candidates.len());
+    let mut higher_better = false;
+    for result in results.into_inner().unwrap() {
+        let (trial, is_higher_better) = result.unwrap()?;
+        trials.push(trial);
+        higher_better = is_higher_better;
+    }
+    Ok((trials, higher_better))
+}
+
+/// Cross-validate a candidate with `num_threads` threads, keeping its
+/// parameters as searched in the trial.
+fn evaluate_one(
+    fold_data: &[(Dataset, Dataset)],
+    params: &TrainParams,
+    num_threads: usize,
+    metric: Option<&str>,
+) -> Result<(Trial, bool)> {
+    let mut job_params = params.clone();
+    job_params.num_threads = Some(num_threads as i32);
+    let result = cv_on_folds(fold_data, &job_params)?;
+    let metric = match metric {
+        Some(metric) => metric,
+        None => result
+            .metrics()
+            .first()
+            .copied()
+            .ok_or_else(|| Error::new("no metric to rank candidates by"))?,
+    };
+    let mean = result
+        .mean(metric)
+        .ok_or_else(|| Error::new(format!("metric '{}' was not evaluated", metric)))?;
+    let higher_better = is_higher_better(metric);
+    let (best, score) = mean
+        .iter()
+        .cloned()
+        .enumerate()
+        .fold(None, |best: Option<(usize, f64)>, (i, value)| match best {
+            Some((_, best_value))
+                if (higher_better && value <= best_value)
+                    || (!higher_better && value >= best_value) =>
+            {
+                best
+            }
+            _ => Some((i, value)),
+        })
+        .ok_or_else(|| Error::new("no iteration was evaluated"))?;
+    let trial = Trial {
+        params: params.clone(),
+        score,
+        best_iteration: best as i32 + 1,
+    };
+    Ok((trial, higher_better))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MetricType, ObjectiveType, TrainParamsBuilder};
+    use serde_json::json;
+
+    #[test]
+    fn grid() {
+        let space = SearchSpace::new()
+            .values("boosting", vec!["gbdt", "dart"])
+            .int_range("num_leaves", 7, 9);
+        let candidates = space.grid().unwrap();
+        assert_eq!(candidates.len(), 6);
+        assert_eq!(
+            candidates[1],
+            vec![
+                (String::from("boosting"), json!("gbdt")),
+                (String::from("num_leaves"), json!(8))
+            ]
+        );
+
+        let space = space.float_range("feature_fraction", 0.5, 1.0);
+        assert!(space.grid().is_err());
+        assert!(SearchSpace::new()
+            .int_range("num_leaves", 9, 7)
+            .validate()
+            .is_err());
+        assert!(SearchSpace::new()
+            .int_range("seed", 0, 1_000_000)
+            .grid()
+            .is_err());
+        assert!(SearchSpace::new()
+            .int_range("seed", 0, 1000)
+            .int_range("num_leaves", 2, 1000)
+            .grid()
+            .is_err());
+    }
+
+    #[test]
+    fn wide_int_range() {
+        assert_eq!(int_span(7, 9), Some(3));
+        assert_eq!(int_span(i64::MIN, i64::MAX), None);
+        assert_eq!(int_span(-1, i64::MAX), None);
+        assert!(SearchSpace::new()
+            .int_range("seed", i64::MIN, i64::MAX)
+            .validate()
+            .is_err());
+
+        let space = SearchSpace::new().int_range("seed", 0, i64::MAX - 1);
+        space.validate().unwrap();
+        for candidate in space.sample(20, 42) {
+            assert!(candidate[0].1.as_i64().unwrap() >= 0);
+        }
+    }
+
+    #[test]
+    fn sample() {
+        let space =
+            SearchSpace::new()
+                .int_range("num_leaves", 7, 9)
+                .log_range("learning_rate", 0.01, 0.3);
+        let candidates = space.sample(50, 42);
+        assert_eq!(candidates.len(), 50);
+        assert_eq!(candidates, space.sample(50, 42));
+        for candidate in candidates.iter() {
+            let num_leaves = candidate[0].1.as_i64().unwrap();
+            assert!((7..=9).contains(&num_leaves));
+            let learning_rate = candidate[1].1.as_f64().unwrap();
+            assert!((0.01..=0.3).contains(&learning_rate));
+        }
+    }
+
+    #[test]
+    fn thread_budget() {
+        assert_eq!(split_threads(8, 1), (1, 8));
+        assert_eq!(split_threads(8, 3), (3, 2));
+        assert_eq!(split_threads(2, 4), (2, 1));
+        assert_eq!(split_threads(0, 0), (1, 1));
+        for num_threads in 1..10 {
+            for jobs in 0..12 {
+                let (jobs, per_job) = split_threads(num_threads, jobs);
+                assert!(per_job >= 1 && jobs * per_job <= num_threads);
+            }
+        }
+    }
+
+    #[test]
+    fn successive_halving() {
+        let dataset = Dataset::from_file(
+            &"lightgbm-sys/lightgbm/examples/binary_classification/binary.train",
+        )
+        .unwrap();
+        let params = TrainParamsBuilder::default()
+            .objective(ObjectiveType::Binary)
+            .metric(vec![MetricType::BinaryLogloss])
+            .num_iterations(20)
+            .num_threads(2)
+            .build()
+            .unwrap();
+        let space = SearchSpace::new().int_range("num_leaves", 2, 5);
+        let trials = Tuner::new(&dataset, params, space, Folds::kfold(3))
+            .successive_halving(5, 2)
+            .jobs(2)
+            .run()
+            .unwrap();
+
+        assert_eq!(trials.len(), 4);
+        let iterations = trials
+            .iter()
+            .map(|t| t.params.num_iterations.unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(iterations, vec![20, 10, 5, 5]);
+        // the per-job thread count does not leak into the searched parameters
+        assert!(trials.iter().all(|t| t.params.num_threads == Some(2)));
+        assert!(trials[2].score <= trials[3].score);
+    }
+}